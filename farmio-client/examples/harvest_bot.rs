@@ -0,0 +1,102 @@
+//! Harvests around its starting position until the Game is over
+//!
+//!     cargo run -p farmio-client --example harvest_bot -- 127.0.0.1:5942 Game1
+//!
+//! In a tournament the `FARMIO_*` environment variables tell where to play,
+//! started by the server it plays over stdin/stdout with the `--stdio` argument,
+//! and takes back its Player with the `FARMIO_PLAYER_TOKEN` in a restored Game.
+
+use std::env;
+
+use farmio_client::{Action, Client, Direction, Joined, Turn, Uuid};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1).peekable();
+    let stdio = args.next_if(|arg| arg == "--stdio").is_some();
+    let addr = args
+        .next()
+        .or_else(|| env::var("FARMIO_ADDR").ok())
+        .unwrap_or_else(|| "127.0.0.1:5942".to_string());
+    let game_name = args
+        .next()
+        .or_else(|| env::var("FARMIO_GAME").ok())
+        .unwrap_or_else(|| "Game1".to_string());
+    let player_name = env::var("FARMIO_PLAYER_NAME").unwrap_or_else(|_| "HarvestBot".to_string());
+    let player_uuid = match env::var("FARMIO_PLAYER_UUID") {
+        Ok(player_uuid) => player_uuid.parse()?,
+        Err(_) => Uuid::new_v4(),
+    };
+    let token = env::var("FARMIO_PLAYER_TOKEN").ok();
+
+    match stdio {
+        true => {
+            play(
+                Client::stdio(),
+                &player_name,
+                player_uuid,
+                &game_name,
+                token,
+            )
+            .await
+        }
+        false => {
+            play(
+                Client::connect(addr).await?,
+                &player_name,
+                player_uuid,
+                &game_name,
+                token,
+            )
+            .await
+        }
+    }
+}
+
+/// Logs to stderr, stdout may be the connection
+async fn play<T: AsyncRead + AsyncWrite + Unpin>(
+    mut client: Client<T>,
+    player_name: &str,
+    player_uuid: Uuid,
+    game_name: &str,
+    token: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // A restored Game waits for its Players to reconnect before it starts again
+    let restored = token.is_some();
+    let joined = client
+        .join_game(player_name, player_uuid, game_name, token, None)
+        .await?;
+    eprintln!("{:?}", joined);
+    if matches!(joined, Joined::Connected { .. }) || restored {
+        if let Some(game_result) = client.wait_for_start().await? {
+            eprintln!("{:?}", game_result);
+            return Ok(());
+        }
+    }
+
+    let directions = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+    for turn in 0.. {
+        let action = match turn % 2 {
+            0 => Action::Harvest,
+            _ => Action::Move {
+                direction: directions[(turn / 2) % directions.len()].clone(),
+            },
+        };
+        match client.act(action).await? {
+            Turn::Played { content, swapped } => {
+                eprintln!("{:?} (swapped: {})", content.result, swapped)
+            }
+            Turn::GameOver(game_result) => {
+                eprintln!("{:?}", game_result);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
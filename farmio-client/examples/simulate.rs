@@ -0,0 +1,71 @@
+//! Plays many seeded Games in-process between two simple Bots
+//!
+//!     cargo run -p farmio-client --example simulate -- 1000
+
+use farmio_client::{Action, Bot, Direction, GameSettings, Observation, Ruleset, Simulator};
+
+/// Harvests, then moves on in the same direction
+struct HarvestBot {
+    direction: Direction,
+}
+
+impl Bot for HarvestBot {
+    fn act(&mut self, observation: &Observation) -> Action {
+        match observation.turn % 2 {
+            0 => Action::Harvest,
+            _ => Action::Move {
+                direction: self.direction.clone(),
+            },
+        }
+    }
+}
+
+struct IdleBot;
+
+impl Bot for IdleBot {
+    fn act(&mut self, _observation: &Observation) -> Action {
+        Action::Idle
+    }
+}
+
+fn main() {
+    let games = std::env::args()
+        .nth(1)
+        .and_then(|games| games.parse::<u64>().ok())
+        .unwrap_or(100);
+
+    let start = std::time::Instant::now();
+    let mut wins = [0u32; 2];
+    for seed in 0..games {
+        let game_settings = GameSettings {
+            seed,
+            max_turns: Some(100),
+            ..GameSettings::default()
+        };
+        let bots: Vec<(String, Box<dyn Bot>)> = vec![
+            (
+                "HarvestBot".to_string(),
+                Box::new(HarvestBot {
+                    direction: Direction::Right,
+                }),
+            ),
+            ("IdleBot".to_string(), Box::new(IdleBot)),
+        ];
+        let game_result = Simulator::new(game_settings, Ruleset::default(), bots)
+            .unwrap_or_else(|err| panic!("Unable to simulate: {}", err))
+            .run();
+        for player_result in game_result.ranking.iter().filter(|r| r.rank == 1) {
+            match player_result.player_name.as_str() {
+                "HarvestBot" => wins[0] += 1,
+                _ => wins[1] += 1,
+            }
+        }
+    }
+    println!(
+        "{} Games in {:?}, wins: HarvestBot {}, IdleBot {}",
+        games,
+        start.elapsed(),
+        wins[0],
+        wins[1]
+    );
+}
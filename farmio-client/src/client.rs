@@ -0,0 +1,237 @@
+use std::fmt;
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, Join, Stdin, Stdout},
+    net::{TcpStream, ToSocketAddrs},
+};
+use tokio_util::codec::{Framed, LinesCodec, LinesCodecError};
+use uuid::Uuid;
+
+use farmio_protocol::{
+    game::{Action, GameResult, GameSettings, MsgToPlayer, MsgToPlayerWithGameContent},
+    lobby::{LobbyMsg, LobbyToPlayer, NewGameSettings},
+    ruleset::Ruleset,
+};
+
+/// Any message the server sends to a Player
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ServerMsg {
+    WithGameContent(MsgToPlayerWithGameContent),
+    Game(MsgToPlayer),
+    Lobby(LobbyToPlayer),
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Connection(LinesCodecError),
+    ConnectionClosed,
+    Json(serde_json::Error),
+    /// The Lobby refused the request
+    Lobby(LobbyToPlayer),
+    /// The Game refused the Player
+    Rejected(MsgToPlayer),
+    UnexpectedMsg(ServerMsg),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Connection(err) => write!(f, "Connection error: `{}`", err),
+            ClientError::ConnectionClosed => write!(f, "Connection closed by the server"),
+            ClientError::Json(err) => write!(f, "Invalid JSON: `{}`", err),
+            ClientError::Lobby(msg) => write!(f, "Refused by the Lobby: `{:?}`", msg),
+            ClientError::Rejected(msg) => write!(f, "Rejected by the Game: `{:?}`", msg),
+            ClientError::UnexpectedMsg(msg) => write!(f, "Unexpected message: `{:?}`", msg),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<LinesCodecError> for ClientError {
+    fn from(err: LinesCodecError) -> Self {
+        ClientError::Connection(err)
+    }
+}
+
+impl From<std::io::Error> for ClientError {
+    fn from(err: std::io::Error) -> Self {
+        ClientError::Connection(err.into())
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(err: serde_json::Error) -> Self {
+        ClientError::Json(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum Joined {
+    Connected {
+        game_settings: Box<GameSettings>,
+        ruleset: Box<Ruleset>,
+        players_connected: u32,
+        /// Keep it, it is required to reconnect
+        token: String,
+    },
+    Reconnected,
+}
+
+#[derive(Debug)]
+pub enum Turn {
+    Played {
+        content: Box<MsgToPlayerWithGameContent>,
+        /// A Swapshroom moved the Player after their previous action,
+        /// so the `cell` received back then is outdated
+        swapped: bool,
+    },
+    GameOver(GameResult),
+}
+
+/// One connection to the server, playing in one Game
+pub struct Client<T = TcpStream> {
+    framed: Framed<T, LinesCodec>,
+    swapped: bool,
+}
+
+impl Client<TcpStream> {
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, ClientError> {
+        let stream = TcpStream::connect(addr).await?;
+        Ok(Self::new(stream))
+    }
+}
+
+impl Client<Join<Stdin, Stdout>> {
+    /// For a BOT started by the server, log to stderr as stdout is the connection
+    pub fn stdio() -> Self {
+        Self::new(tokio::io::join(tokio::io::stdin(), tokio::io::stdout()))
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> Client<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            framed: Framed::new(transport, LinesCodec::new()),
+            swapped: false,
+        }
+    }
+
+    pub async fn new_game(
+        &mut self,
+        player_name: &str,
+        player_uuid: Uuid,
+        game_name: &str,
+        game_settings: Option<GameSettings>,
+    ) -> Result<Joined, ClientError> {
+        // The password of the `GameSettings` is only sent in a `NewGame`
+        self.send(&LobbyMsg::NewGame {
+            player_name: player_name.to_string(),
+            player_uuid,
+            game_name: game_name.to_string(),
+            game_settings: game_settings.map(NewGameSettings::from),
+        })
+        .await?;
+        self.recv_joined().await
+    }
+
+    /// `token` is required to reconnect, `password` to join a Game created with one
+    pub async fn join_game(
+        &mut self,
+        player_name: &str,
+        player_uuid: Uuid,
+        game_name: &str,
+        token: Option<String>,
+        password: Option<String>,
+    ) -> Result<Joined, ClientError> {
+        self.send(&LobbyMsg::JoinGame {
+            player_name: player_name.to_string(),
+            player_uuid,
+            game_name: game_name.to_string(),
+            token,
+            password,
+        })
+        .await?;
+        self.recv_joined().await
+    }
+
+    /// Returns the result of the Game, if it was over before it could start
+    pub async fn wait_for_start(&mut self) -> Result<Option<GameResult>, ClientError> {
+        loop {
+            match self.recv().await? {
+                ServerMsg::Game(MsgToPlayer::GameStarted) => return Ok(None),
+                ServerMsg::Game(MsgToPlayer::GameOver(game_result)) => {
+                    return Ok(Some(game_result))
+                }
+                ServerMsg::Game(MsgToPlayer::WaitingOtherPlayersToJoin) => {}
+                msg => return Err(ClientError::UnexpectedMsg(msg)),
+            }
+        }
+    }
+
+    /// Sends the Action of the turn and waits for its result, tagged with its turn,
+    /// the `Swapped` messages arriving in between are folded into it
+    pub async fn act(&mut self, action: Action) -> Result<Turn, ClientError> {
+        self.send(&action).await?;
+        loop {
+            match self.recv().await? {
+                ServerMsg::WithGameContent(content) => {
+                    return Ok(Turn::Played {
+                        content: Box::new(content),
+                        swapped: std::mem::take(&mut self.swapped),
+                    })
+                }
+                ServerMsg::Game(MsgToPlayer::Swapped) => self.swapped = true,
+                ServerMsg::Game(MsgToPlayer::GameOver(game_result)) => {
+                    return Ok(Turn::GameOver(game_result))
+                }
+                ServerMsg::Game(MsgToPlayer::GameStarted)
+                | ServerMsg::Game(MsgToPlayer::WaitingOtherPlayersToJoin)
+                | ServerMsg::Game(MsgToPlayer::TurnStarted { .. })
+                | ServerMsg::Game(MsgToPlayer::TurnEnded { .. }) => {}
+                ServerMsg::Lobby(msg) => return Err(ClientError::Lobby(msg)),
+                msg => return Err(ClientError::UnexpectedMsg(msg)),
+            }
+        }
+    }
+
+    pub async fn send<M: Serialize>(&mut self, msg: &M) -> Result<(), ClientError> {
+        self.framed.send(serde_json::to_string(msg)?).await?;
+        Ok(())
+    }
+
+    pub async fn recv(&mut self) -> Result<ServerMsg, ClientError> {
+        match self.framed.next().await {
+            Some(line) => Ok(serde_json::from_str(&line?)?),
+            None => Err(ClientError::ConnectionClosed),
+        }
+    }
+
+    async fn recv_joined(&mut self) -> Result<Joined, ClientError> {
+        loop {
+            match self.recv().await? {
+                ServerMsg::Lobby(LobbyToPlayer::GameCreated) => {}
+                ServerMsg::Lobby(msg) => return Err(ClientError::Lobby(msg)),
+                ServerMsg::Game(MsgToPlayer::Connected {
+                    game_settings,
+                    ruleset,
+                    players_connected,
+                    token,
+                }) => {
+                    return Ok(Joined::Connected {
+                        game_settings,
+                        ruleset,
+                        players_connected,
+                        token,
+                    })
+                }
+                ServerMsg::Game(MsgToPlayer::Reconnected) => return Ok(Joined::Reconnected),
+                ServerMsg::Game(msg) => return Err(ClientError::Rejected(msg)),
+                msg => return Err(ClientError::UnexpectedMsg(msg)),
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Typed async client of the Farmio server, for writing BOTs in Rust
+//!
+//! The message types are shared with the server through `farmio-protocol`,
+//! so they can not drift apart from `msg.json`.
+
+mod client;
+
+pub use client::{Client, ClientError, Joined, ServerMsg, Turn};
+#[cfg(feature = "simulator")]
+pub use farmio::simulator::{Bot, Observation, Simulator};
+pub use farmio_protocol::{
+    cell::Cell,
+    direction::Direction,
+    game::{
+        Action, BlockedBy, GameOverReason, GamePhase, GameResult, GameSettings, GameStatus,
+        MsgToPlayer, MsgToPlayerWithGameContent, PlayerResult, VisiblePlayer,
+    },
+    ground::Ground,
+    harvest::Harvest,
+    lobby::{LobbyMsg, LobbyToPlayer, NewGameSettings},
+    plant::Plant,
+    pos::Pos,
+    ruleset::Ruleset,
+    seed::Seed,
+    topology::Topology,
+};
+pub use uuid::Uuid;
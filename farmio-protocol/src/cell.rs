@@ -1,18 +1,26 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{
-    ground::Ground,
-    plant::{Cactus, Cane, Plant, Tree, Wheat},
-};
+use crate::{ground::Ground, plant::Plant, ruleset::Ruleset};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cell {
     pub ground: Ground,
     pub plant: Plant,
 }
 
 impl Cell {
-    pub fn to_ansi(&self) -> [String; 8] {
+    pub fn to_ansi(&self, ruleset: &Ruleset) -> [String; 8] {
+        let (background, foreground, subcells) = self.ansi_parts(ruleset);
+        subcells.map(|subcell| {
+            format!(
+                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
+                background, foreground, subcell
+            )
+        })
+    }
+
+    /// The 256 colors background and foreground codes, and the 2 lines of 4 characters
+    pub fn ansi_parts(&self, ruleset: &Ruleset) -> (u8, u8, [char; 8]) {
         let background = match self.ground {
             Ground::Dirt => 94,
             Ground::Tiled => 22,
@@ -25,7 +33,7 @@ impl Cell {
             Plant::None => (0, [' '; 8]),
             Plant::Wheat(wheat) => {
                 let g = to_chars3(wheat.growth);
-                let m = to_chars3(Wheat::GROWTH_TO_GRAINS);
+                let m = to_chars3(ruleset.wheat.growth_to_grains);
                 (184, ['W', g[0], g[1], g[2], '/', m[0], m[1], m[2]])
             }
             Plant::Bush(bush) => {
@@ -35,12 +43,12 @@ impl Cell {
             }
             Plant::Tree(tree) => {
                 let g = to_chars3(tree.growth);
-                let m = to_chars3(Tree::GROWTH_TO_WOOD);
+                let m = to_chars3(ruleset.tree.growth_to_wood);
                 (70, ['T', g[0], g[1], g[2], '/', m[0], m[1], m[2]])
             }
             Plant::Cane(cane) => {
                 let g = to_chars3(cane.growth);
-                let m = to_chars3(Cane::GROWTH_TO_SUGAR);
+                let m = to_chars3(ruleset.cane.growth_to_sugar);
                 (0, ['C', g[0], g[1], g[2], '/', m[0], m[1], m[2]])
             }
             Plant::Pumpkin(pumpkin) => {
@@ -71,7 +79,7 @@ impl Cell {
                         '+',
                         to_char(cactus.size),
                         '/',
-                        to_char(Cactus::MAX_CACTUSMEAT),
+                        to_char(ruleset.cactus.max_cactusmeat),
                     ],
                 )
             }
@@ -99,40 +107,7 @@ impl Cell {
                 (11, ['S', g[0], g[1], g[2], 's', r[0], r[1], r[2]])
             }
         };
-        [
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[0]
-            ),
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[1]
-            ),
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[2]
-            ),
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[3]
-            ),
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[4]
-            ),
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[5]
-            ),
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[6]
-            ),
-            format!(
-                "\x1b[48;5;{}m\x1b[38;5;{}m{}\x1b[0m",
-                background, foreground, subcells[7]
-            ),
-        ]
+        (background, foreground, subcells)
     }
 }
 
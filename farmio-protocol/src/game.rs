@@ -0,0 +1,323 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    cell::Cell, direction::Direction, harvest::Harvest, map_generator::MapGenerator, pos::Pos,
+    ruleset::Ruleset, seed::Seed, topology::Topology,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Action {
+    Idle,
+    Move { direction: Direction },
+    Harvest,
+    Plant { seed: Seed },
+    Trade { seed: Seed, volume: u32 },
+    Till,
+    Look,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum MsgToPlayer {
+    // Admin //
+    Connected {
+        game_settings: Box<GameSettings>,
+        ruleset: Box<Ruleset>,
+        players_connected: u32,
+        /// Keep it secret, it is required to reconnect
+        token: String,
+    },
+    AlreadyConnected,
+    InvalidToken,
+    WrongPassword,
+    Reconnected,
+    WaitingOtherPlayersToJoin,
+    GameIsFull,
+    GameStarted,
+    GameSaved,
+    UnableToSaveGame,
+    // Turn //
+    /// The Actions are collected until the deadline, in `deadline_ms` from now
+    TurnStarted {
+        turn: u32,
+        deadline_ms: u32,
+    },
+    /// Every result of the turn was sent before it
+    TurnEnded {
+        turn: u32,
+    },
+    /// A later Action of the Player replaced the one of the request `id` in this turn
+    ActionOverwritten {
+        id: Option<u64>,
+    },
+    // Idle //
+    Idled,
+    // Move //
+    Moved,
+    BlockedBy(BlockedBy),
+    // Harvest //
+    Harvested {
+        harvest: Harvest,
+        volume: u32,
+    },
+    NoHarvest,
+    // Plant //
+    Planted,
+    NotEnoughSeed,
+    WrongGroundType,
+    CannotPlantOver,
+    // Trade //
+    Traded,
+    NotEnoughHarvest,
+    InvalidTrade,
+    // Till //
+    Tilled,
+    //WrongGroundType,
+    // Look //
+    Looked {
+        /// Rows around the Player, indexed by `[dy + radius][dx + radius]`,
+        /// `None` beyond the edge of a bounded map
+        cells: Vec<Vec<Option<Cell>>>,
+        players: Vec<VisiblePlayer>,
+    },
+    // Forced Move //
+    Swapped, // When a palyer receive it they should read again the TCP buffer,
+    // because it was sent in the previous round as an extra message,
+    // (in case of single thread player)
+    // End //
+    GameOver(GameResult),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GameOverReason {
+    MaxTurnsReached,
+    TargetScoreReached,
+    AllPlayersDisconnected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerResult {
+    pub rank: u32,
+    /// Only known to the server, the Players are not told the UUIDs of the others
+    #[serde(skip)]
+    pub player_uuid: Uuid,
+    pub player_name: String,
+    pub points: u32,
+    pub harvests: HashMap<Harvest, u32>,
+    pub seeds: HashMap<Seed, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameResult {
+    pub reason: GameOverReason,
+    pub turns: u32,
+    pub ranking: Vec<PlayerResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum BlockedBy {
+    AnotherPlayer,
+    WallBush,
+    Swapshroom,
+    /// The edge of a bounded map
+    Edge,
+}
+
+/// Another Player seen with `Look`, `offset` is relative to the looking Player
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VisiblePlayer {
+    pub player_name: String,
+    pub offset: Pos,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MsgToPlayerWithGameContent {
+    /// The turn in which the Action was played
+    pub turn: u32,
+    /// Of the request of the Action, if it had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    pub result: MsgToPlayer,
+    pub cell: Cell,
+    pub harvests: HashMap<Harvest, u32>,
+    pub seeds: HashMap<Seed, u32>,
+    pub points: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    pub number_of_players: u32,
+    pub turn_duration_ms: u32,
+    pub map_size: u32,
+    /// Of a rectangular map, `map_size` if `None`
+    pub map_width: Option<u32>,
+    pub map_height: Option<u32>,
+    pub topology: Topology,
+    pub seed: u64,
+    pub map_generator: MapGenerator,
+    /// Name of a map file of the Server, the size, `map_generator` and `symmetric_map`
+    /// are ignored then
+    pub map_file: Option<String>,
+    /// Rotates the map around its center, so each of the 2 or 4 Players
+    /// starts with the same surroundings, the `map_size` has to be a multiple of 4
+    pub symmetric_map: bool,
+    /// How far a Player can see around them with `Look`
+    pub visibility_radius: u32,
+    /// Anyone can `Spectate` the Game, only the admin otherwise,
+    /// the Spectators see the whole map regardless of the `visibility_radius`
+    pub public_spectating: bool,
+    // End conditions
+    pub max_turns: Option<u32>,
+    pub target_score: Option<u32>,
+    pub end_when_all_disconnected: bool,
+    pub save_every_n_turns: Option<u32>,
+    /// BOTs of the Server to start in the Game, they play over stdin/stdout
+    pub bots: Vec<String>,
+    /// Name of a ruleset file of the Server, the built-in rules if `None`
+    pub ruleset: Option<String>,
+    /// Inventory of every Player at the start of the Game
+    pub starting_harvests: BTreeMap<Harvest, u32>,
+    pub starting_seeds: BTreeMap<Seed, u32>,
+    /// Required to join the Game, never sent back to the Players
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            number_of_players: 2,
+            turn_duration_ms: 1000,
+            map_size: 16,
+            map_width: None,
+            map_height: None,
+            topology: Topology::Torus,
+            seed: 0,
+            map_generator: MapGenerator::Shuffled,
+            symmetric_map: false,
+            map_file: None,
+            visibility_radius: 2,
+            public_spectating: false,
+            max_turns: None,
+            target_score: None,
+            end_when_all_disconnected: true,
+            save_every_n_turns: None,
+            bots: Vec::new(),
+            ruleset: None,
+            starting_harvests: BTreeMap::new(),
+            starting_seeds: BTreeMap::from([(Seed::Wheat, 4)]),
+            password: None,
+        }
+    }
+}
+
+impl GameSettings {
+    /// Larger starting volumes could overflow the inventories during the Game
+    pub const MAX_STARTING_VOLUME: u32 = 1_000_000;
+    /// The whole map is generated, copied and sent every turn
+    pub const MAX_MAP_SIZE: u32 = 1024;
+    pub const MAX_PLAYERS: u32 = 256;
+
+    /// `(width, height)` of the map to generate
+    pub fn map_dimensions(&self) -> (u32, u32) {
+        (
+            self.map_width.unwrap_or(self.map_size),
+            self.map_height.unwrap_or(self.map_size),
+        )
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.number_of_players == 0 {
+            return Err("The `number_of_players` can not be 0".to_string());
+        }
+        if self.number_of_players > Self::MAX_PLAYERS {
+            return Err(format!(
+                "The `number_of_players` can not be higher than `{}`",
+                Self::MAX_PLAYERS
+            ));
+        }
+        let sizes = [Some(self.map_size), self.map_width, self.map_height];
+        if sizes
+            .into_iter()
+            .flatten()
+            .any(|size| size > Self::MAX_MAP_SIZE)
+        {
+            return Err(format!(
+                "The `map_size`, `map_width` and `map_height` can not be higher than `{}`",
+                Self::MAX_MAP_SIZE
+            ));
+        }
+        let (width, height) = self.map_dimensions();
+        if self.map_file.is_none() && (width == 0 || height == 0) {
+            return Err("The map can not be empty".to_string());
+        }
+        if self.map_file.is_none() {
+            self.validate_visibility_radius(width as usize, height as usize)?;
+            // Each Player starts on their own Stone, a map file is checked for them when loaded
+            if width * height < self.number_of_players {
+                return Err(format!(
+                    "The map of `{}` Cells is too small for `{}` Players",
+                    width * height,
+                    self.number_of_players
+                ));
+            }
+        }
+        if self.bots.len() > self.number_of_players as usize {
+            return Err("There can not be more `bots` than `number_of_players`".to_string());
+        }
+        if self.symmetric_map && self.map_file.is_none() {
+            if self.number_of_players != 2 && self.number_of_players != 4 {
+                return Err("A symmetric map is for 2 or 4 Players".to_string());
+            }
+            if width != height || !width.is_multiple_of(4) {
+                return Err(
+                    "A symmetric map has to be square, with a size multiple of 4".to_string(),
+                );
+            }
+        }
+        let volumes = self
+            .starting_harvests
+            .values()
+            .chain(self.starting_seeds.values());
+        for volume in volumes {
+            if *volume > Self::MAX_STARTING_VOLUME {
+                return Err(format!(
+                    "A starting volume can not be higher than `{}`",
+                    Self::MAX_STARTING_VOLUME
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Half the map is seen from anywhere, a larger `Look` would only repeat the Cells
+    pub fn validate_visibility_radius(&self, width: usize, height: usize) -> Result<(), String> {
+        let max_radius = width.max(height).div_ceil(2);
+        if self.visibility_radius as usize > max_radius {
+            return Err(format!(
+                "The `visibility_radius` can not be higher than `{}` on this map",
+                max_radius
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GamePhase {
+    Waiting,
+    Running,
+    Finished,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameStatus {
+    pub game_name: String,
+    pub game_settings: GameSettings,
+    pub players_connected: u32,
+    pub turn: u32,
+    pub phase: GamePhase,
+}
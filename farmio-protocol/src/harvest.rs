@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Harvest {
+    Grains,
+    Berry,
+    Wood,
+    Sugar,
+    PumpkinSeed,
+    CactusMeat,
+    Power,
+}
@@ -0,0 +1,14 @@
+//! The messages of the Farmio protocol, shared by the server and the clients
+
+pub mod cell;
+pub mod direction;
+pub mod game;
+pub mod ground;
+pub mod harvest;
+pub mod lobby;
+pub mod map_generator;
+pub mod plant;
+pub mod pos;
+pub mod ruleset;
+pub mod seed;
+pub mod topology;
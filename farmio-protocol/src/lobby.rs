@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::game::{GameSettings, GameStatus};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum LobbyMsg {
+    NewGame {
+        player_name: String,
+        player_uuid: Uuid,
+        game_name: String,
+        game_settings: Option<NewGameSettings>,
+    },
+    JoinGame {
+        player_name: String,
+        player_uuid: Uuid,
+        game_name: String,
+        /// Received in `Connected`, required to reconnect
+        token: Option<String>,
+        password: Option<String>,
+    },
+    ListGames,
+    GameInfo {
+        game_name: String,
+    },
+    /// Only of a Game with `public_spectating`, unless with the `admin_token`
+    Spectate {
+        game_name: String,
+        #[serde(default)]
+        admin_token: Option<String>,
+    },
+    // Admin //
+    SaveGameState {
+        game_name: String,
+        admin_token: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum LobbyToPlayer {
+    GameCreated,
+    /// Empty, or with `/`, `\` or `.` in it, the files of the Game are named after it
+    InvalidGameName,
+    GameAlreadyExists,
+    TooManyGames,
+    UnknownBot(String),
+    InvalidRuleset,
+    InvalidMap,
+    InvalidGameSettings(String),
+    GameNotExists,
+    GameList(Vec<GameStatus>),
+    GameInfo(Box<GameStatus>),
+    NotConnectedToAnyGame,
+    UnableToCommunicateWithGame,
+    NotAuthorized,
+    InvalidMsg,
+}
+
+/// The `GameSettings` of a `NewGame` with their `password`, which `GameSettings` never sends back
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewGameSettings {
+    #[serde(flatten)]
+    pub game_settings: GameSettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+impl NewGameSettings {
+    pub fn into_game_settings(self) -> GameSettings {
+        GameSettings {
+            password: self.password,
+            ..self.game_settings
+        }
+    }
+}
+
+impl From<GameSettings> for NewGameSettings {
+    fn from(game_settings: GameSettings) -> Self {
+        Self {
+            password: game_settings.password.clone(),
+            game_settings,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// How the Cells of a new map are arranged, the share of each Ground is the same
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum MapGenerator {
+    /// Every Cell anywhere, uniformly
+    #[default]
+    Shuffled,
+    /// Lakes, deserts and fields, from smoothed noise wrapping around the edges
+    Clustered,
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::direction::Direction;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Pos {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Pos {
+    pub fn get_next_pos_on_map(
+        &self,
+        direction: Option<Direction>,
+        width: i32,
+        height: i32,
+    ) -> Self {
+        match direction {
+            Some(direction) => {
+                // The map is Wrapping around, it's a Torus 🍩
+                let dp = direction.to_pos();
+                Self {
+                    x: (self.x + dp.x).rem_euclid(width),
+                    y: (self.y + dp.y).rem_euclid(height),
+                }
+            }
+            None => self.clone(),
+        }
+    }
+
+    pub fn get_offset_pos_on_map(&self, offset: &Pos, width: i32, height: i32) -> Self {
+        Self {
+            x: (self.x + offset.x).rem_euclid(width),
+            y: (self.y + offset.y).rem_euclid(height),
+        }
+    }
+
+    /// Shortest offset from `self` to `other`, going around the Torus if it is shorter
+    pub fn get_offset_to_on_map(&self, other: &Pos, width: i32, height: i32) -> Self {
+        let wrap = |d: i32, size: i32| {
+            let d = d.rem_euclid(size);
+            match d > size / 2 {
+                true => d - size,
+                false => d,
+            }
+        };
+        Self {
+            x: wrap(other.x - self.x, width),
+            y: wrap(other.y - self.y, height),
+        }
+    }
+}
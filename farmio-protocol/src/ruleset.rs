@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ground::Ground, harvest::Harvest, seed::Seed};
+
+/// The numbers of the Plants and the trades, the built-in rules are the `Default`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ruleset {
+    pub growth_rate: u8,
+    /// Growth rate next to Water
+    pub water_growth_rate: u8,
+    pub wheat: WheatRules,
+    pub bush: BushRules,
+    pub tree: TreeRules,
+    pub cane: CaneRules,
+    pub pumpkin: PumpkinRules,
+    pub cactus: CactusRules,
+    pub wallbush: WallbushRules,
+    pub swapshroom: SwapshroomRules,
+    pub sunflower: SunflowerRules,
+    /// Harvests paid for one Seed, the Seeds missing here can not be traded
+    pub trades: BTreeMap<Seed, BTreeMap<Harvest, u32>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WheatRules {
+    pub growth_to_grains: u8,
+    pub grains_yield: u32,
+    pub points_per_grains: u32,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BushRules {
+    pub growth_to_wood: u8,
+    pub wood_yield: u32,
+    pub points_per_wood: u32,
+    pub growth_per_berries: u8,
+    pub max_berries: u8,
+    pub points_per_berries: u32,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TreeRules {
+    pub growth_to_wood: u8,
+    pub wood_yield: u32,
+    pub points_per_wood: u32,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaneRules {
+    pub growth_to_sugar: u8,
+    pub sugar_yield: u32,
+    pub points_per_sugar: u32,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PumpkinRules {
+    /// Per size, a Pumpkin yields `size * size` Pumpkin seeds
+    pub growth_to_pumpkinseed: u8,
+    pub points_per_pumpkinseed: u32,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CactusRules {
+    pub growth_per_cactusmeat: u8,
+    pub max_cactusmeat: u8,
+    pub points_per_cactusmeat: u32,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WallbushRules {
+    pub growth_to_be_ready: u8,
+    pub max_health: u8,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwapshroomRules {
+    pub growth_to_be_ready: u8,
+    pub grounds: Vec<Ground>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SunflowerRules {
+    pub growth_to_power: u8,
+    pub power_yield: u32,
+    pub points_per_power: u32,
+    /// Planting one plants a Sunflower on every Stone
+    pub grounds: Vec<Ground>,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self {
+            growth_rate: 1,
+            water_growth_rate: 2,
+            wheat: WheatRules::default(),
+            bush: BushRules::default(),
+            tree: TreeRules::default(),
+            cane: CaneRules::default(),
+            pumpkin: PumpkinRules::default(),
+            cactus: CactusRules::default(),
+            wallbush: WallbushRules::default(),
+            swapshroom: SwapshroomRules::default(),
+            sunflower: SunflowerRules::default(),
+            trades: BTreeMap::from([
+                (Seed::Bush, BTreeMap::from([(Harvest::Grains, 4)])),
+                (Seed::Tree, BTreeMap::from([(Harvest::Wood, 4)])),
+                (Seed::Cane, BTreeMap::from([(Harvest::Grains, 2)])),
+                (
+                    Seed::Pumpkin,
+                    BTreeMap::from([(Harvest::Berry, 8), (Harvest::Wood, 16)]),
+                ),
+                (
+                    Seed::Cactus,
+                    BTreeMap::from([(Harvest::Sugar, 9), (Harvest::Wood, 16)]),
+                ),
+                (Seed::Wallbush, BTreeMap::from([(Harvest::PumpkinSeed, 10)])),
+                (Seed::Swapshroom, BTreeMap::from([(Harvest::CactusMeat, 5)])),
+                (
+                    Seed::Sunflower,
+                    BTreeMap::from([(Harvest::PumpkinSeed, 50), (Harvest::CactusMeat, 27)]),
+                ),
+            ]),
+        }
+    }
+}
+
+impl Default for WheatRules {
+    fn default() -> Self {
+        Self {
+            growth_to_grains: 8,
+            grains_yield: 1,
+            points_per_grains: 1,
+            grounds: vec![Ground::Dirt, Ground::Tiled],
+        }
+    }
+}
+
+impl Default for BushRules {
+    fn default() -> Self {
+        Self {
+            growth_to_wood: 10,
+            wood_yield: 1,
+            points_per_wood: 1,
+            growth_per_berries: 4,
+            max_berries: 4,
+            points_per_berries: 2,
+            grounds: vec![Ground::Tiled],
+        }
+    }
+}
+
+impl Default for TreeRules {
+    fn default() -> Self {
+        Self {
+            growth_to_wood: 16,
+            wood_yield: 16,
+            points_per_wood: 1,
+            grounds: vec![Ground::Dirt],
+        }
+    }
+}
+
+impl Default for CaneRules {
+    fn default() -> Self {
+        Self {
+            growth_to_sugar: 10,
+            sugar_yield: 3,
+            points_per_sugar: 2,
+            grounds: vec![Ground::Sand],
+        }
+    }
+}
+
+impl Default for PumpkinRules {
+    fn default() -> Self {
+        Self {
+            growth_to_pumpkinseed: 4,
+            points_per_pumpkinseed: 5,
+            grounds: vec![Ground::Tiled],
+        }
+    }
+}
+
+impl Default for CactusRules {
+    fn default() -> Self {
+        Self {
+            growth_per_cactusmeat: 6,
+            max_cactusmeat: 3,
+            points_per_cactusmeat: 10,
+            grounds: vec![Ground::Sand],
+        }
+    }
+}
+
+impl Default for WallbushRules {
+    fn default() -> Self {
+        Self {
+            growth_to_be_ready: 8,
+            max_health: 42,
+            grounds: vec![Ground::Tiled],
+        }
+    }
+}
+
+impl Default for SwapshroomRules {
+    fn default() -> Self {
+        Self {
+            growth_to_be_ready: 8,
+            grounds: vec![
+                Ground::Dirt,
+                Ground::Tiled,
+                Ground::Sand,
+                Ground::Water,
+                Ground::Stone,
+            ],
+        }
+    }
+}
+
+impl Default for SunflowerRules {
+    fn default() -> Self {
+        Self {
+            growth_to_power: 30,
+            power_yield: 1,
+            points_per_power: 1024,
+            grounds: vec![Ground::Stone],
+        }
+    }
+}
+
+impl Ruleset {
+    /// The growths are `u8`s, they must not overflow
+    pub fn validate(&self) -> Result<(), String> {
+        let rate = self.growth_rate.max(self.water_growth_rate) as u32;
+        let max_growths = [
+            ("wheat", self.wheat.growth_to_grains as u32),
+            (
+                "bush",
+                self.bush.growth_to_wood as u32
+                    + self.bush.growth_per_berries as u32 * self.bush.max_berries as u32,
+            ),
+            ("tree", self.tree.growth_to_wood as u32),
+            ("cane", self.cane.growth_to_sugar as u32),
+            // A Pumpkin grows up to 5 times, with 4 neighbours
+            ("pumpkin", self.pumpkin.growth_to_pumpkinseed as u32 * 5),
+            (
+                "cactus",
+                self.cactus.growth_per_cactusmeat as u32 * self.cactus.max_cactusmeat as u32,
+            ),
+            ("wallbush", self.wallbush.growth_to_be_ready as u32),
+            ("swapshroom", self.swapshroom.growth_to_be_ready as u32),
+            ("sunflower", self.sunflower.growth_to_power as u32),
+        ];
+        for (plant, max_growth) in max_growths {
+            if max_growth + rate > u8::MAX as u32 {
+                return Err(format!("The growth of `{}` is too high", plant));
+            }
+        }
+        if self.bush.growth_per_berries == 0
+            || self.pumpkin.growth_to_pumpkinseed == 0
+            || self.cactus.growth_per_cactusmeat == 0
+        {
+            return Err("The growth per harvest can not be 0".to_string());
+        }
+        // Above its size a Pumpkin shrinks by the growth rate, down to `growth_to_pumpkinseed`
+        if (self.pumpkin.growth_to_pumpkinseed as u32) < rate {
+            return Err(format!(
+                "The `growth_to_pumpkinseed` can not be lower than the growth rate `{}`",
+                rate
+            ));
+        }
+        for rate in [self.growth_rate, self.water_growth_rate] {
+            if self.max_berries_grown(rate) > u8::MAX as u32 {
+                return Err("The berries of `bush` can grow too many".to_string());
+            }
+        }
+        if self.trades.values().any(|trade| trade.is_empty()) {
+            return Err("A trade has to cost something".to_string());
+        }
+        Ok(())
+    }
+
+    /// Without a harvest, from a new Bush or from a harvested one, as `Map::update_map` grows it
+    fn max_berries_grown(&self, rate: u8) -> u32 {
+        let (rate, rules) = (rate as u32, &self.bush);
+        let growth_to_wood = rules.growth_to_wood as u32;
+        let full = growth_to_wood + rules.growth_per_berries as u32 * rules.max_berries as u32;
+        if rate == 0 {
+            return 0;
+        }
+        [0, growth_to_wood]
+            .into_iter()
+            .map(|mut growth| {
+                let mut berries = 0;
+                while growth < full {
+                    let ripe = growth >= growth_to_wood;
+                    growth += rate;
+                    if ripe {
+                        berries += (growth - growth_to_wood) / rules.growth_per_berries as u32;
+                    }
+                }
+                berries
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn grounds(&self, seed: &Seed) -> &[Ground] {
+        match seed {
+            Seed::Wheat => &self.wheat.grounds,
+            Seed::Bush => &self.bush.grounds,
+            Seed::Tree => &self.tree.grounds,
+            Seed::Cane => &self.cane.grounds,
+            Seed::Pumpkin => &self.pumpkin.grounds,
+            Seed::Cactus => &self.cactus.grounds,
+            Seed::Wallbush => &self.wallbush.grounds,
+            Seed::Swapshroom => &self.swapshroom.grounds,
+            Seed::Sunflower => &self.sunflower.grounds,
+        }
+    }
+}
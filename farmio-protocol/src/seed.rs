@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Seed {
+    Wheat,
+    Bush,
+    Tree,
+    Cane,
+    Pumpkin,
+    Cactus,
+    Wallbush,
+    Swapshroom,
+    Sunflower,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// What is beyond the edges of the map
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Topology {
+    /// The other side of the map
+    #[default]
+    Torus,
+    /// Nothing, the Players can not leave the map
+    Bounded,
+}
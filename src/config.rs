@@ -0,0 +1,141 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::GameSettings,
+    map::Map,
+    ruleset::{self, Ruleset},
+    snapshot::SnapshotOutput,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub ip: String,
+    pub port: u16,
+    /// Port of the WebSocket listener, on the same `ip`, disabled if `None`
+    pub websocket_port: Option<u16>,
+    /// Port of the HTTP viewer of the Games, it needs the WebSocket listener
+    pub http_port: Option<u16>,
+    pub max_games: usize,
+    pub output_dir: PathBuf,
+    pub default_game_settings: GameSettings,
+    /// Required by the admin commands, they are disabled without it
+    pub admin_token: Option<String>,
+    /// Save files of the Games to restore on startup
+    pub restore: Vec<PathBuf>,
+    /// BOT programs (with their arguments) by name, started by the `bots` of `GameSettings`
+    pub bots: BTreeMap<String, Vec<String>>,
+    /// A BOT not answering its Game in time is reported, one not reading its input is stopped
+    pub bot_timeout_ms: u64,
+    /// Directory of the ruleset files, selected by the `ruleset` of `GameSettings`
+    pub rulesets_dir: PathBuf,
+    /// Directory of the map files, selected by the `map_file` of `GameSettings`
+    pub maps_dir: PathBuf,
+    /// JSON snapshots of every turn of the Games, in the `output_dir`
+    pub snapshots: Option<SnapshotOutput>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            ip: "127.0.0.1".to_string(),
+            port: 5942,
+            websocket_port: None,
+            http_port: None,
+            max_games: 16,
+            output_dir: PathBuf::from("."),
+            default_game_settings: GameSettings::default(),
+            admin_token: None,
+            restore: Vec::new(),
+            bots: BTreeMap::new(),
+            bot_timeout_ms: 5000,
+            rulesets_dir: PathBuf::from("rulesets"),
+            maps_dir: PathBuf::from("maps"),
+            snapshots: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn load(path: &Path) -> Self {
+        let content = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("Unable to read config file `{:?}`: `{}`", path, err));
+        let config: Self = serde_json::from_str(&content)
+            .unwrap_or_else(|err| panic!("Invalid config file `{:?}`: `{}`", path, err));
+        if let Some((bot_name, _)) = config.bots.iter().find(|(_, command)| command.is_empty()) {
+            panic!(
+                "BOT `{}` needs a command in config file `{:?}`!",
+                bot_name, path
+            );
+        }
+        if let Some(SnapshotOutput::Directory { keep: Some(0) }) = config.snapshots {
+            panic!(
+                "The snapshots need a `keep` of at least 1 in config file `{:?}`, leave it unset to keep every turn!",
+                path
+            );
+        }
+        if let Err(err) = config.default_game_settings.validate() {
+            panic!(
+                "Invalid `default_game_settings` in config file `{:?}`: {}",
+                path, err
+            );
+        }
+        config
+    }
+
+    /// The built-in rules if the Game has no ruleset
+    pub fn load_ruleset(&self, game_settings: &GameSettings) -> Result<Ruleset, String> {
+        match &game_settings.ruleset {
+            Some(name) => ruleset::load(&self.rulesets_dir, name),
+            None => Ok(Ruleset::default()),
+        }
+    }
+
+    /// `None` if the map of the Game is generated
+    pub fn load_map(
+        &self,
+        game_settings: &GameSettings,
+        ruleset: &Ruleset,
+    ) -> Result<Option<Map>, String> {
+        match &game_settings.map_file {
+            Some(name) => {
+                let map = Map::load(
+                    &self.maps_dir,
+                    name,
+                    game_settings.number_of_players,
+                    ruleset,
+                )?;
+                game_settings.validate_visibility_radius(map.width(), map.height())?;
+                Ok(Some(map))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn ip_port(&self) -> String {
+        format!("{}:{}", self.ip, self.port)
+    }
+
+    pub fn websocket_ip_port(&self) -> Option<String> {
+        self.websocket_port
+            .map(|websocket_port| format!("{}:{}", self.ip, websocket_port))
+    }
+
+    pub fn http_ip_port(&self) -> Option<String> {
+        self.http_port
+            .map(|http_port| format!("{}:{}", self.ip, http_port))
+    }
+}
+
+/// `<dir>/<name>.<extension>`, the name can not leave the directory
+pub fn named_file(dir: &Path, name: &str, extension: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains(['/', '\\', '.']) {
+        return Err(format!("Invalid name `{}`", name));
+    }
+    Ok(dir.join(format!("{}.{}", name, extension)))
+}
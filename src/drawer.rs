@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use tokio::{
     fs::{File, OpenOptions},
@@ -11,15 +11,15 @@ pub struct Drawer {
 }
 
 impl Drawer {
-    pub async fn new(game_name: String) -> Self {
-        let file_name = format!("{}.farmio", game_name);
+    pub async fn new(output_dir: &Path, game_name: &str) -> Self {
+        let file_name = output_dir.join(format!("{}.farmio", game_name));
         let file = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
-            .open(file_name.clone())
+            .open(&file_name)
             .await
-            .unwrap_or_else(|_| panic!("Unable to Open file: {}", file_name));
+            .unwrap_or_else(|_| panic!("Unable to Open file: {:?}", file_name));
         let file = Arc::new(Mutex::new(file));
         Self { file }
     }
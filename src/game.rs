@@ -1,12 +1,15 @@
-use rand::{rngs::SmallRng, Rng, SeedableRng};
-use serde::{Deserialize, Serialize};
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
 use std::{
-    collections::{hash_map::Entry, HashMap, HashSet},
+    collections::{btree_map::Entry, hash_map, BTreeMap, BTreeSet, HashMap, HashSet},
     time::Duration,
 };
 use tokio::{
-    sync::mpsc::{Receiver, Sender},
-    time::timeout,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        watch,
+    },
+    time::{timeout_at, Instant},
 };
 use uuid::Uuid;
 
@@ -21,139 +24,316 @@ use crate::{
     plant::{Bush, Cactus, Cane, Plant, Pumpkin, Sunflower, Swapshroom, Tree, Wallbush, Wheat},
     player::Player,
     pos::Pos,
+    replay::{ReplayHeader, ReplayPlayer, ReplayRecorder, ReplayTurn},
+    ruleset::Ruleset,
+    save::{GameSave, GameSaver, SavedPlayer},
     seed::Seed,
-    send_to_player::send_msg_to_player,
+    send_to_player::{send_msg_to_player, send_reply_to_player},
+    snapshot::{GameSnapshot, PlayerState, Snapshotter},
+    spectator::{send_to_spectators, MsgToSpectator, PlayerSnapshot, TurnSnapshot},
 };
 
-#[derive(Debug, Deserialize)]
-pub enum Action {
-    Idle,
-    Move {
-        direction: Direction,
-    },
-    Harvest,
-    Plant {
-        seed: Seed,
-    },
-    Trade {
-        seed: Seed,
-        volume: u32,
-    },
-    Till,
-    #[serde(skip_deserializing)]
-    __Connect__ {
+pub use farmio_protocol::game::{
+    Action, BlockedBy, GameOverReason, GamePhase, GameResult, GameSettings, GameStatus,
+    MsgToPlayer, MsgToPlayerWithGameContent, PlayerResult, VisiblePlayer,
+};
+
+/// What a connection sends to its Game, the Action of its Player or a request of the Server
+#[derive(Debug)]
+pub enum GameMsg {
+    Action(Action),
+    Connect {
         player_name: String,
         to_player_tx: Sender<String>,
+        /// Required to reconnect
+        token: Option<String>,
+        /// Required to join a Game with a password
+        password: Option<String>,
     },
-    #[serde(skip_deserializing)]
-    __Disconnect__,
-}
-
-#[derive(Debug, Serialize)]
-pub enum MsgToPlayer {
-    // Admin //
-    Connected {
-        game_settings: GameSettings,
-        players_connected: u32,
+    Disconnect,
+    Save {
+        to_player_tx: Sender<String>,
     },
-    AlreadyConnected,
-    Reconnected,
-    WaitingOtherPlayersToJoin,
-    GameIsFull,
-    GameStarted,
-    // Idle //
-    Idled,
-    // Move //
-    Moved,
-    BlockedBy(BlockedBy),
-    // Harvest //
-    Harvested {
-        harvest: Harvest,
-        volume: u32,
+    Spectate {
+        to_spectator_tx: Sender<String>,
     },
-    NoHarvest,
-    // Plant //
-    Planted,
-    NotEnoughSeed,
-    WrongGroundType,
-    CannotPlantOver,
-    // Trade //
-    Traded,
-    NotEnoughHarvest,
-    InvalidTrade,
-    // Till //
-    Tilled,
-    //WrongGroundType,
-    // Forced Move //
-    Swapped, // When a palyer receive it they should read again the TCP buffer,
-             // because it was sent in the previous round as an extra message,
-             // (in case of single thread player)
-}
-
-#[derive(Debug, Serialize)]
-pub enum BlockedBy {
-    AnotherPlayer,
-    WallBush,
-    Swapshroom,
-}
-
-#[derive(Debug, Serialize)]
-pub struct MsgToPlayerWithGameContent {
-    result: MsgToPlayer,
-    cell: Cell,
-    harvests: HashMap<Harvest, u32>,
-    seeds: HashMap<Seed, u32>,
-    points: u32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GameSettings {
-    number_of_players: u32,
-    turn_duration_ms: u32,
-    map_size: u32,
-    seed: u64,
 }
 
 pub struct Game {
     game_name: String,
     to_game_rx: Receiver<PlayerAction>,
     game_settings: GameSettings,
+    ruleset: Ruleset,
     turns: u32,
-    rng: SmallRng,
-    players: HashMap<Uuid, Player>,
+    rng: Xoshiro256PlusPlus,
+    players: BTreeMap<Uuid, Player>,
+    /// Join order of the Players, the base of the turn order
+    player_order: Vec<Uuid>,
     map: Map,
-    drawer: Drawer,
-    active_swapshrooms: HashMap<u32, (Pos, Pos)>,
+    drawer: Option<Drawer>,
+    recorder: Option<ReplayRecorder>,
+    saver: Option<GameSaver>,
+    snapshotter: Option<Snapshotter>,
+    /// Restored from a save, waiting for the Players to reconnect
+    restored: bool,
+    spectators: Vec<Sender<String>>,
+    phase: GamePhase,
+    status_tx: watch::Sender<GameStatus>,
+    active_swapshrooms: BTreeMap<u32, (Pos, Pos)>,
+    /// The configured BOTs started for the Game by the UUID they join with
+    bots: BTreeMap<Uuid, String>,
 }
 
 impl Game {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         game_name: String,
         to_game_rx: Receiver<PlayerAction>,
-        game_settings: GameSettings,
+        mut game_settings: GameSettings,
+        ruleset: Ruleset,
+        map: Option<Map>,
+        mut drawer: Option<Drawer>,
+        recorder: Option<ReplayRecorder>,
+        saver: Option<GameSaver>,
+        snapshotter: Option<Snapshotter>,
     ) -> Self {
-        // TODO: Check if all players could fit in the map
-        let mut rng = rand::rngs::SmallRng::seed_from_u64(game_settings.seed);
-        let players = HashMap::new();
-        let mut drawer = Drawer::new(game_name.clone()).await;
-        let map = Map::generate_map(
-            game_settings.map_size as usize,
-            &mut rng,
-            game_settings.number_of_players,
-        );
-        map.print_map_with_players(&mut drawer, &HashMap::new())
-            .await;
-        let swapshrooms = HashMap::new();
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(game_settings.seed);
+        let players = BTreeMap::new();
+        let map = match map {
+            Some(mut map) => {
+                map.set_topology(game_settings.topology.clone());
+                game_settings.map_size = map.width() as u32;
+                game_settings.map_width = Some(map.width() as u32);
+                game_settings.map_height = Some(map.height() as u32);
+                map
+            }
+            None => Map::generate_map(&game_settings, &mut rng, &ruleset),
+        };
+        if let Some(drawer) = &mut drawer {
+            map.print_map_with_players(drawer, &HashMap::new(), &ruleset)
+                .await;
+        }
+        let swapshrooms = BTreeMap::new();
+        let (status_tx, _status_rx) = watch::channel(GameStatus {
+            game_name: game_name.clone(),
+            game_settings: game_settings.clone(),
+            players_connected: 0,
+            turn: 0,
+            phase: GamePhase::Waiting,
+        });
         Self {
             game_name,
             to_game_rx,
             game_settings,
+            ruleset,
             turns: 0,
             rng,
             players,
+            player_order: Vec::new(),
             map,
             drawer,
+            recorder,
+            saver,
+            snapshotter,
+            restored: false,
+            spectators: Vec::new(),
+            phase: GamePhase::Waiting,
+            status_tx,
             active_swapshrooms: swapshrooms,
+            bots: BTreeMap::new(),
+        }
+    }
+
+    pub async fn restore(
+        game_save: GameSave,
+        to_game_rx: Receiver<PlayerAction>,
+        mut drawer: Option<Drawer>,
+        recorder: Option<ReplayRecorder>,
+        saver: Option<GameSaver>,
+        snapshotter: Option<Snapshotter>,
+    ) -> Self {
+        let mut game_settings = game_save.game_settings;
+        game_settings.password = game_save.password;
+        let mut players = BTreeMap::new();
+        let mut player_order = Vec::new();
+        let mut bots = BTreeMap::new();
+        for saved_player in game_save.players {
+            if let Some(bot_name) = saved_player.bot {
+                bots.insert(saved_player.player_uuid, bot_name);
+            }
+            // Dropped at once, the Player gets a new one when reconnecting
+            let (to_player_tx, _to_player_rx) = mpsc::channel::<String>(1);
+            let mut player = Player::new(
+                saved_player.player_name,
+                to_player_tx,
+                saved_player.pos,
+                saved_player.harvests,
+                saved_player.seeds,
+            );
+            player.points = saved_player.points;
+            player.next_swapshroom_pair_id = saved_player.next_swapshroom_pair_id;
+            player.token = saved_player.token;
+            player.connected = false;
+            player_order.push(saved_player.player_uuid);
+            players.insert(saved_player.player_uuid, player);
+        }
+        if let Some(drawer) = &mut drawer {
+            game_save
+                .map
+                .print_map_with_players(
+                    drawer,
+                    &players
+                        .values()
+                        .map(|p| (p.pos.clone(), p.player_name.clone()))
+                        .collect(),
+                    &game_save.ruleset,
+                )
+                .await;
+        }
+        let (status_tx, _status_rx) = watch::channel(GameStatus {
+            game_name: game_save.game_name.clone(),
+            game_settings: game_settings.clone(),
+            players_connected: 0,
+            turn: game_save.turns,
+            phase: GamePhase::Waiting,
+        });
+        Self {
+            game_name: game_save.game_name,
+            to_game_rx,
+            game_settings,
+            ruleset: game_save.ruleset,
+            turns: game_save.turns,
+            rng: game_save.rng,
+            players,
+            player_order,
+            map: game_save.map,
+            drawer,
+            recorder,
+            saver,
+            snapshotter,
+            restored: true,
+            spectators: Vec::new(),
+            phase: GamePhase::Waiting,
+            status_tx,
+            active_swapshrooms: game_save.active_swapshrooms,
+            bots,
+        }
+    }
+
+    /// The BOT will join as `player_uuid`, so it is relaunched if the Game is restored
+    pub fn add_bot(&mut self, player_uuid: Uuid, bot_name: String) {
+        self.bots.insert(player_uuid, bot_name);
+    }
+
+    fn to_save(&self) -> GameSave {
+        let players = self
+            .player_order
+            .iter()
+            .map(|player_uuid| {
+                let player = &self.players[player_uuid];
+                SavedPlayer {
+                    player_uuid: player_uuid.to_owned(),
+                    player_name: player.player_name.clone(),
+                    pos: player.pos.clone(),
+                    harvests: player.harvests.clone(),
+                    seeds: player.seeds.clone(),
+                    points: player.points,
+                    next_swapshroom_pair_id: player.next_swapshroom_pair_id,
+                    token: player.token.clone(),
+                    bot: self.bots.get(player_uuid).cloned(),
+                }
+            })
+            .collect();
+        GameSave {
+            game_name: self.game_name.clone(),
+            game_settings: self.game_settings.clone(),
+            password: self.game_settings.password.clone(),
+            ruleset: self.ruleset.clone(),
+            turns: self.turns,
+            rng: self.rng.clone(),
+            players,
+            map: self.map.clone(),
+            active_swapshrooms: self.active_swapshrooms.clone(),
+        }
+    }
+
+    fn add_spectator(&mut self, to_spectator_tx: Sender<String>) {
+        println!("{} Spectator joined", self.p());
+        let mut spectators = vec![to_spectator_tx];
+        send_to_spectators(
+            &mut spectators,
+            &MsgToSpectator::Spectating {
+                game_settings: self.game_settings.clone(),
+                ruleset: Box::new(self.ruleset.clone()),
+            },
+        );
+        send_to_spectators(&mut spectators, &MsgToSpectator::Turn(self.turn_snapshot()));
+        self.spectators.append(&mut spectators);
+    }
+
+    fn turn_snapshot(&self) -> TurnSnapshot {
+        TurnSnapshot {
+            turn: self.turns,
+            map: self.map.cells().to_owned(),
+            players: self
+                .players
+                .values()
+                .map(|player| PlayerSnapshot {
+                    player_name: player.player_name.clone(),
+                    pos: player.pos.clone(),
+                    points: player.points,
+                    connected: player.connected,
+                })
+                .collect(),
+        }
+    }
+
+    pub async fn write_snapshot(&mut self) {
+        let Some(snapshotter) = &mut self.snapshotter else {
+            return;
+        };
+        let players = self
+            .player_order
+            .iter()
+            .map(|player_uuid| {
+                let player = &self.players[player_uuid];
+                PlayerState {
+                    player_name: player.player_name.clone(),
+                    pos: player.pos.clone(),
+                    harvests: player.harvests.clone().into_iter().collect(),
+                    seeds: player.seeds.clone().into_iter().collect(),
+                    points: player.points,
+                    connected: player.connected,
+                }
+            })
+            .collect();
+        snapshotter
+            .write(&GameSnapshot {
+                game_name: self.game_name.clone(),
+                turn: self.turns,
+                width: self.map.width(),
+                height: self.map.height(),
+                topology: self.map.topology().clone(),
+                map: self.map.cells().to_owned(),
+                players,
+            })
+            .await;
+    }
+
+    async fn save(&self) -> bool {
+        let Some(saver) = &self.saver else {
+            eprintln!("{} has no save file", self.p());
+            return false;
+        };
+        match saver.save(&self.to_save()).await {
+            Ok(()) => {
+                println!("{} Game Saved", self.p());
+                true
+            }
+            Err(err) => {
+                eprintln!("{} Unable to save the Game: `{}`", self.p(), err);
+                false
+            }
         }
     }
 
@@ -167,66 +347,244 @@ impl Game {
         )
     }
 
-    pub async fn run(&mut self) {
-        self.wait_for_connections().await;
-        self.game_loop().await;
-        // TODO: Kill the game if all players are disconnecting
+    pub fn game_name(&self) -> &str {
+        &self.game_name
+    }
+
+    pub fn turns(&self) -> u32 {
+        self.turns
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn players(&self) -> &BTreeMap<Uuid, Player> {
+        &self.players
+    }
+
+    pub fn player_order(&self) -> &[Uuid] {
+        &self.player_order
     }
 
-    async fn wait_for_connections(&mut self) {
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn status(&self) -> watch::Receiver<GameStatus> {
+        self.status_tx.subscribe()
+    }
+
+    /// Only the connection which connected the Player can act in their name
+    fn is_from_player_connection(&self, player_action: &PlayerAction) -> bool {
+        self.players
+            .get(&player_action.player_uuid)
+            .is_some_and(|player| player.connection_id == player_action.connection_id)
+    }
+
+    fn publish_status(&self) {
+        self.status_tx.send_replace(GameStatus {
+            game_name: self.game_name.clone(),
+            game_settings: self.game_settings.clone(),
+            players_connected: self.players.values().filter(|p| p.connected).count() as u32,
+            turn: self.turns,
+            phase: self.phase.clone(),
+        });
+    }
+
+    pub async fn run(&mut self) -> GameResult {
+        let reason = match self.wait_for_connections().await {
+            Some(reason) => reason,
+            None => {
+                if !self.restored {
+                    self.record_header().await;
+                    self.write_snapshot().await;
+                }
+                self.game_loop().await
+            }
+        };
+        self.game_over(reason).await
+    }
+
+    pub fn add_player(
+        &mut self,
+        player_uuid: Uuid,
+        player_name: String,
+        to_player_tx: Sender<String>,
+        pos: Pos,
+    ) -> &mut Player {
+        self.player_order.push(player_uuid);
+        let harvests = self
+            .game_settings
+            .starting_harvests
+            .clone()
+            .into_iter()
+            .collect();
+        let seeds = self
+            .game_settings
+            .starting_seeds
+            .clone()
+            .into_iter()
+            .collect();
+        self.players.entry(player_uuid).or_insert(Player::new(
+            player_name,
+            to_player_tx,
+            pos,
+            harvests,
+            seeds,
+        ))
+    }
+
+    /// The first Stone without a Player on it, where the next Player starts
+    pub fn free_spot(&self) -> Option<Pos> {
+        let player_positions = self
+            .players
+            .values()
+            .map(|player| player.pos.clone())
+            .collect::<BTreeSet<Pos>>();
+        self.map
+            .get_stones()
+            .difference(&player_positions)
+            .next()
+            .cloned()
+    }
+
+    /// Returns the reason if the Game ended before it could start
+    async fn wait_for_connections(&mut self) -> Option<GameOverReason> {
         while let Some(player_action) = self.to_game_rx.recv().await {
             let p = self.p();
-            if let Action::__Connect__ {
+            let from_player_connection = self.is_from_player_connection(&player_action);
+            if let GameMsg::Connect {
                 player_name,
                 mut to_player_tx,
+                token,
+                password,
             } = player_action.action
             {
                 let players_connected = self.players.len() as u32;
-                let player_positions = self
-                    .players
-                    .values()
-                    .map(|player| player.pos.clone())
-                    .collect();
+                let game_is_full = players_connected >= self.game_settings.number_of_players;
+                let free_spot = self.free_spot();
+                let wrong_password = self.game_settings.password.is_some()
+                    && self.game_settings.password != password;
                 match self.players.entry(player_action.player_uuid) {
+                    Entry::Occupied(occupied_entry) if !occupied_entry.get().has_token(&token) => {
+                        eprintln!("{} Player `{}` sent an invalid token", p, player_name);
+                        send_reply_to_player(
+                            &mut to_player_tx,
+                            player_action.request_id,
+                            MsgToPlayer::InvalidToken,
+                        )
+                        .await;
+                    }
+                    Entry::Occupied(occupied_entry) if !occupied_entry.get().connected => {
+                        let player = occupied_entry.into_mut();
+                        println!("{} Player `{}` Reconnected", p, player_name);
+                        player.to_player_tx = to_player_tx;
+                        player.connection_id = player_action.connection_id;
+                        player.connected = true;
+                        send_reply_to_player(
+                            &mut player.to_player_tx,
+                            player_action.request_id,
+                            MsgToPlayer::Reconnected,
+                        )
+                        .await;
+                    }
                     Entry::Occupied(_occupied_entry) => {
                         eprintln!("{} Player `{}` Already Connected", p, player_name,);
-                        send_msg_to_player(&mut to_player_tx, MsgToPlayer::AlreadyConnected).await;
+                        send_reply_to_player(
+                            &mut to_player_tx,
+                            player_action.request_id,
+                            MsgToPlayer::AlreadyConnected,
+                        )
+                        .await;
                     }
-                    Entry::Vacant(vacant_entry) => {
-                        let stones = self.map.get_stones();
-                        let mut free_spots = stones.difference(&player_positions);
-
-                        match free_spots.next() {
-                            Some(pos) => {
-                                let player = vacant_entry.insert(Player::new(
-                                    player_name,
-                                    to_player_tx,
-                                    pos.to_owned(),
-                                ));
-                                println!("{} Player `{}` Connected", p, player.player_name);
-                                send_msg_to_player(
-                                    &mut player.to_player_tx,
-                                    MsgToPlayer::Connected {
-                                        game_settings: self.game_settings.clone(),
-                                        players_connected: players_connected + 1,
-                                    },
-                                )
-                                .await;
-                            }
-                            None => {
-                                println!(
-                                    "{} No free spots left in the map for Player `{}`",
-                                    p, player_name
-                                );
-                                send_msg_to_player(&mut to_player_tx, MsgToPlayer::GameIsFull)
-                                    .await;
-                            }
-                        }
+                    Entry::Vacant(_vacant_entry) if wrong_password => {
+                        eprintln!("{} Player `{}` sent a wrong password", p, player_name);
+                        send_reply_to_player(
+                            &mut to_player_tx,
+                            player_action.request_id,
+                            MsgToPlayer::WrongPassword,
+                        )
+                        .await;
+                    }
+                    Entry::Vacant(_vacant_entry) if game_is_full => {
+                        println!("{} The Game is full for Player `{}`", p, player_name);
+                        send_reply_to_player(
+                            &mut to_player_tx,
+                            player_action.request_id,
+                            MsgToPlayer::GameIsFull,
+                        )
+                        .await;
                     }
+                    Entry::Vacant(_vacant_entry) => match free_spot {
+                        Some(pos) => {
+                            let game_settings = self.game_settings.clone();
+                            let ruleset = Box::new(self.ruleset.clone());
+                            let player = self.add_player(
+                                player_action.player_uuid,
+                                player_name,
+                                to_player_tx,
+                                pos,
+                            );
+                            player.connection_id = player_action.connection_id;
+                            println!("{} Player `{}` Connected", p, player.player_name);
+                            send_reply_to_player(
+                                &mut player.to_player_tx,
+                                player_action.request_id,
+                                MsgToPlayer::Connected {
+                                    game_settings: Box::new(game_settings),
+                                    ruleset,
+                                    players_connected: players_connected + 1,
+                                    token: player.token.clone(),
+                                },
+                            )
+                            .await;
+                        }
+                        None => {
+                            println!(
+                                "{} No free spots left in the map for Player `{}`",
+                                p, player_name
+                            );
+                            send_reply_to_player(
+                                &mut to_player_tx,
+                                player_action.request_id,
+                                MsgToPlayer::GameIsFull,
+                            )
+                            .await;
+                        }
+                    },
                 }
-                if self.players.len() as u32 == self.game_settings.number_of_players {
+                self.publish_status();
+                if self.players.len() as u32 == self.game_settings.number_of_players
+                    && self.players.values().all(|p| p.connected)
+                {
                     break;
                 }
+            } else if let GameMsg::Save { mut to_player_tx } = player_action.action {
+                send_msg_to_player(&mut to_player_tx, MsgToPlayer::WaitingOtherPlayersToJoin).await;
+            } else if let GameMsg::Spectate { to_spectator_tx } = player_action.action {
+                self.add_spectator(to_spectator_tx);
+            } else if !from_player_connection {
+                eprintln!("Player `{}` is not connected and sent non __Connect__ Action `{:?}` in wait_for_connections pahse in Game `{}` ", 
+                    player_action.player_uuid, player_action.action, self.game_name);
+            } else if let (GameMsg::Disconnect, true) = (&player_action.action, self.restored) {
+                // A restored Player keeps their spot and state
+                if let Some(player) = self.players.get_mut(&player_action.player_uuid) {
+                    player.connected = false;
+                    println!("{} Player `{}` disconnected!", p, player.player_name);
+                }
+                self.publish_status();
+            } else if let GameMsg::Disconnect = player_action.action {
+                // The spot is freed up for another Player
+                if let Some(player) = self.players.remove(&player_action.player_uuid) {
+                    self.player_order
+                        .retain(|player_uuid| *player_uuid != player_action.player_uuid);
+                    println!("{} Player `{}` left the Game", p, player.player_name);
+                }
+                self.publish_status();
+                if self.players.is_empty() && self.game_settings.end_when_all_disconnected {
+                    return Some(GameOverReason::AllPlayersDisconnected);
+                }
             } else if let Some(player) = self.players.get_mut(&player_action.player_uuid) {
                 println!(
                     "Player `{}` sent non __Connect__ Action `{:?}` in wait_for_connections phase in Game `{}`",
@@ -237,9 +595,6 @@ impl Game {
                     MsgToPlayer::WaitingOtherPlayersToJoin,
                 )
                 .await;
-            } else {
-                eprintln!("Player `{}` is not connected and sent non __Connect__ Action `{:?}` in wait_for_connections pahse in Game `{}` ", 
-                    player_action.player_uuid, player_action.action, self.game_name);
             }
         }
 
@@ -254,53 +609,208 @@ impl Game {
         for (_player_uuid, player) in self.players.iter_mut() {
             send_msg_to_player(&mut player.to_player_tx, MsgToPlayer::GameStarted).await;
         }
+        self.phase = GamePhase::Running;
+        self.publish_status();
+        None
     }
 
-    async fn game_loop(&mut self) {
+    async fn game_loop(&mut self) -> GameOverReason {
         let turn_duration = Duration::from_millis(self.game_settings.turn_duration_ms as u64);
 
         loop {
-            // TODO: collect actions
-            //       process actions
-            //       update map
-            //       send back the results
-            //       log the state (or actions, for replay)
+            let turn = self.turns;
+            self.send_to_connected_players(MsgToPlayer::TurnStarted {
+                turn,
+                deadline_ms: self.game_settings.turn_duration_ms,
+            })
+            .await;
             let player_actions = self.collect_player_actions(turn_duration).await;
-            self.process_player_actions(player_actions).await;
-            self.map.update_map(&mut self.active_swapshrooms);
+            self.play_turn(player_actions).await;
+            self.send_to_connected_players(MsgToPlayer::TurnEnded { turn })
+                .await;
+            if let Some(n) = self.game_settings.save_every_n_turns {
+                if n > 0 && self.turns.is_multiple_of(n) {
+                    self.save().await;
+                }
+            }
+            if let Some(reason) = self.check_end_conditions() {
+                return reason;
+            }
+        }
+    }
+
+    pub async fn play_turn(&mut self, player_actions: BTreeMap<Uuid, Action>) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder
+                .write(&ReplayTurn {
+                    turn: self.turns,
+                    actions: player_actions.clone(),
+                })
+                .await;
+        }
+        self.process_player_actions(player_actions).await;
+        self.map
+            .update_map(&mut self.active_swapshrooms, &self.ruleset);
+        if let Some(drawer) = &mut self.drawer {
             self.map
                 .print_map_with_players(
-                    &mut self.drawer,
+                    drawer,
                     &self
                         .players
                         .values()
                         .map(|p| (p.pos.clone(), p.player_name.clone()))
                         .collect(),
+                    &self.ruleset,
                 )
                 .await;
-            // TODO: end if all players are disconnected
-            // TODO: End the game if a player reaches a certain score
-            self.turns += 1;
+        }
+        self.turns += 1;
+        self.write_snapshot().await;
+        self.publish_status();
+        if !self.spectators.is_empty() {
+            let msg_to_spectator = MsgToSpectator::Turn(self.turn_snapshot());
+            send_to_spectators(&mut self.spectators, &msg_to_spectator);
         }
     }
 
-    async fn collect_player_actions(&mut self, turn_duration: Duration) -> HashMap<Uuid, Action> {
+    async fn record_header(&mut self) {
+        if let Some(recorder) = &mut self.recorder {
+            let players = self
+                .player_order
+                .iter()
+                .map(|player_uuid| {
+                    let player = &self.players[player_uuid];
+                    ReplayPlayer {
+                        player_uuid: player_uuid.to_owned(),
+                        player_name: player.player_name.clone(),
+                        pos: player.pos.clone(),
+                    }
+                })
+                .collect();
+            recorder
+                .write(&ReplayHeader {
+                    game_name: self.game_name.clone(),
+                    game_settings: self.game_settings.clone(),
+                    ruleset: self.ruleset.clone(),
+                    map: self
+                        .game_settings
+                        .map_file
+                        .as_ref()
+                        .map(|_| self.map.clone()),
+                    players,
+                })
+                .await;
+        }
+    }
+
+    pub fn check_end_conditions(&self) -> Option<GameOverReason> {
+        if let Some(max_turns) = self.game_settings.max_turns {
+            if self.turns >= max_turns {
+                return Some(GameOverReason::MaxTurnsReached);
+            }
+        }
+        if let Some(target_score) = self.game_settings.target_score {
+            if self.players.values().any(|p| p.points >= target_score) {
+                return Some(GameOverReason::TargetScoreReached);
+            }
+        }
+        if self.game_settings.end_when_all_disconnected
+            && self.players.values().all(|p| !p.connected)
+        {
+            return Some(GameOverReason::AllPlayersDisconnected);
+        }
+        None
+    }
+
+    pub async fn game_over(&mut self, reason: GameOverReason) -> GameResult {
+        let mut players = self.players.iter().collect::<Vec<(&Uuid, &Player)>>();
+        players.sort_by_key(|(_, p)| std::cmp::Reverse(p.points));
+        let mut ranking = Vec::<PlayerResult>::with_capacity(players.len());
+        for (i, (player_uuid, player)) in players.into_iter().enumerate() {
+            // Players with the same points share the rank
+            let rank = match ranking.last() {
+                Some(prev) if prev.points == player.points => prev.rank,
+                _ => i as u32 + 1,
+            };
+            ranking.push(PlayerResult {
+                rank,
+                player_uuid: *player_uuid,
+                player_name: player.player_name.clone(),
+                points: player.points,
+                harvests: player.harvests.clone(),
+                seeds: player.seeds.clone(),
+            });
+        }
+        let game_result = GameResult {
+            reason,
+            turns: self.turns,
+            ranking,
+        };
+
+        self.phase = GamePhase::Finished;
+        self.publish_status();
+        println!("{} Game Over: `{:?}`", self.p(), game_result);
+        send_to_spectators(
+            &mut self.spectators,
+            &MsgToSpectator::GameOver(game_result.clone()),
+        );
+        for player in self.players.values_mut().filter(|p| p.connected) {
+            send_msg_to_player(
+                &mut player.to_player_tx,
+                MsgToPlayer::GameOver(game_result.clone()),
+            )
+            .await;
+        }
+        game_result
+    }
+
+    async fn send_to_connected_players(&mut self, msg_to_player: MsgToPlayer) {
+        for player in self.players.values_mut().filter(|p| p.connected) {
+            send_msg_to_player(&mut player.to_player_tx, &msg_to_player).await;
+        }
+    }
+
+    /// Until the deadline of the turn, the other messages do not push it back
+    async fn collect_player_actions(
+        &mut self,
+        turn_duration: Duration,
+    ) -> BTreeMap<Uuid, Action> {
         let p = self.p();
-        let mut player_actions = HashMap::<Uuid, Action>::new();
-        while let Ok(Some(player_action)) = timeout(turn_duration, self.to_game_rx.recv()).await {
+        let deadline = Instant::now() + turn_duration;
+        let mut player_actions = BTreeMap::<Uuid, Action>::new();
+        while let Ok(Some(player_action)) = timeout_at(deadline, self.to_game_rx.recv()).await {
+            let from_player_connection = self.is_from_player_connection(&player_action);
             match player_action.action {
-                Action::__Connect__ {
+                GameMsg::Connect {
                     player_name,
                     mut to_player_tx,
+                    token,
+                    password: _,
                 } => {
                     match self.players.entry(player_action.player_uuid) {
+                        Entry::Occupied(occupied_entry)
+                            if !occupied_entry.get().has_token(&token) =>
+                        {
+                            eprintln!("{} Player `{}` sent an invalid token", p, player_name);
+                            send_reply_to_player(
+                                &mut to_player_tx,
+                                player_action.request_id,
+                                MsgToPlayer::InvalidToken,
+                            )
+                            .await;
+                        }
                         Entry::Occupied(occupied_entry) => {
                             let player = occupied_entry.into_mut();
                             println!("{} Player `{}` Reconnected", p, player_name);
                             player.to_player_tx = to_player_tx;
+                            player.connection_id = player_action.connection_id;
                             player.connected = true;
-                            send_msg_to_player(&mut player.to_player_tx, MsgToPlayer::Reconnected)
-                                .await;
+                            send_reply_to_player(
+                                &mut player.to_player_tx,
+                                player_action.request_id,
+                                MsgToPlayer::Reconnected,
+                            )
+                            .await;
                         }
                         Entry::Vacant(_vacant_entry) => {
                             // This is unreachable, because the Player guard on top of the game_loop
@@ -308,23 +818,63 @@ impl Game {
                                             "{} Player `{}` tried to connect to the game, but it is already full!",
                                             p, player_name
                                         );
-                            send_msg_to_player(&mut to_player_tx, MsgToPlayer::GameIsFull).await;
+                            send_reply_to_player(
+                                &mut to_player_tx,
+                                player_action.request_id,
+                                MsgToPlayer::GameIsFull,
+                            )
+                            .await;
                         }
                     }
+                    self.publish_status();
                 }
-                Action::__Disconnect__ => match self.players.entry(player_action.player_uuid) {
-                    Entry::Occupied(occupied_entry) => {
-                        let player = occupied_entry.into_mut();
-                        player.connected = false;
-                        println!("{} Player `{}` disconnected!", p, player.player_name)
-                    }
-                    Entry::Vacant(_vacant_entry) => {
-                        eprintln!("{} Unkonw Player tried to disconnect", p)
+                GameMsg::Disconnect => {
+                    match self.players.entry(player_action.player_uuid) {
+                        Entry::Occupied(_occupied_entry) if !from_player_connection => {
+                            // An older connection of a reconnected Player
+                        }
+                        Entry::Occupied(occupied_entry) => {
+                            let player = occupied_entry.into_mut();
+                            player.connected = false;
+                            println!("{} Player `{}` disconnected!", p, player.player_name)
+                        }
+                        Entry::Vacant(_vacant_entry) => {
+                            eprintln!("{} Unkonw Player tried to disconnect", p)
+                        }
                     }
-                },
-                action => {
+                    self.publish_status();
+                }
+                GameMsg::Save { mut to_player_tx } => {
+                    let msg_to_player = match self.save().await {
+                        true => MsgToPlayer::GameSaved,
+                        false => MsgToPlayer::UnableToSaveGame,
+                    };
+                    send_msg_to_player(&mut to_player_tx, msg_to_player).await;
+                }
+                GameMsg::Spectate { to_spectator_tx } => self.add_spectator(to_spectator_tx),
+                GameMsg::Action(action) if !from_player_connection => {
+                    eprintln!(
+                        "{} Action `{:?}` of Player `{}` is not from their connection!",
+                        p, action, player_action.player_uuid
+                    );
+                }
+                GameMsg::Action(action) => {
                     // Players can overwrite their own action
-                    player_actions.insert(player_action.player_uuid, action);
+                    let Some(player) = self.players.get_mut(&player_action.player_uuid) else {
+                        continue;
+                    };
+                    let overwritten_id =
+                        std::mem::replace(&mut player.request_id, player_action.request_id);
+                    if player_actions
+                        .insert(player_action.player_uuid, action)
+                        .is_some()
+                    {
+                        send_msg_to_player(
+                            &mut player.to_player_tx,
+                            MsgToPlayer::ActionOverwritten { id: overwritten_id },
+                        )
+                        .await;
+                    }
 
                     // If all player did an action we can fastforward to the processing of the turn
                     if player_actions.len() == self.players.len() {
@@ -336,77 +886,141 @@ impl Game {
         player_actions
     }
 
-    async fn process_player_actions(&mut self, player_actions: HashMap<Uuid, Action>) {
+    /// The Players take turns in their join order,
+    /// rotated by one each turn, so nobody is always the first
+    fn turn_order(&self) -> Vec<Uuid> {
+        let mut turn_order = self.player_order.clone();
+        if !turn_order.is_empty() {
+            let first = self.turns as usize % turn_order.len();
+            turn_order.rotate_left(first);
+        }
+        turn_order
+    }
+
+    async fn process_player_actions(&mut self, mut player_actions: BTreeMap<Uuid, Action>) {
         let p = self.p();
-        let mut next_positions = HashMap::<Pos, Vec<Uuid>>::new();
+        let turn = self.turns;
+        let mut next_positions = BTreeMap::<Pos, Vec<Uuid>>::new();
         let mut moving_players = Vec::<Uuid>::new();
         let mut swap_players = Vec::<(Pos, Pos)>::new();
+        // `Look` sees the other Players where they were at the start of the turn
+        let player_positions = self
+            .players
+            .iter()
+            .map(|(player_uuid, player)| {
+                (*player_uuid, player.player_name.clone(), player.pos.clone())
+            })
+            .collect::<Vec<_>>();
 
-        for (player_uuid, action) in player_actions {
-            let player = match self.players.get_mut(&player_uuid) {
-                Some(player) => player,
-                None => {
-                    eprintln!("{} Player `{}` tried to do Action `{:?}`, but they are not Connected to the Game!", p, player_uuid, action);
-                    continue;
-                }
+        for player_uuid in self.turn_order() {
+            let Some(action) = player_actions.remove(&player_uuid) else {
+                continue;
+            };
+            let Some(player) = self.players.get_mut(&player_uuid) else {
+                continue;
             };
             match action {
                 Action::Idle => {
-                    msg_to_player_with_game_content(&self.map, player, MsgToPlayer::Idled).await
+                    msg_to_player_with_game_content(&self.map, player, turn, MsgToPlayer::Idled)
+                        .await
                 }
                 Action::Move { direction } => {
                     moving_players.push(player_uuid);
                     action_move_collection(
                         &self.map,
                         player,
+                        turn,
                         player_uuid,
                         Some(direction),
                         &mut next_positions,
-                    );
+                    )
+                    .await;
                 }
                 Action::Harvest => {
                     action_harvest(
                         &mut self.map,
                         player,
+                        turn,
                         &mut self.active_swapshrooms,
                         &mut swap_players,
+                        &self.ruleset,
                     )
                     .await
                 }
                 Action::Plant { seed } => {
-                    action_plant(&mut self.map, player, seed, &mut self.rng).await
+                    action_plant(
+                        &mut self.map,
+                        player,
+                        turn,
+                        seed,
+                        &mut self.rng,
+                        &self.ruleset,
+                    )
+                    .await
                 }
                 Action::Trade { seed, volume } => {
-                    action_trade(&mut self.map, player, seed, volume).await
+                    action_trade(&mut self.map, player, turn, seed, volume, &self.ruleset).await
+                }
+                Action::Till => action_till(&mut self.map, player, turn).await,
+                Action::Look => {
+                    action_look(
+                        &self.map,
+                        player,
+                        turn,
+                        player_uuid,
+                        &player_positions,
+                        self.game_settings.visibility_radius as i32,
+                    )
+                    .await
                 }
-                Action::Till => action_till(&mut self.map, player).await,
-                Action::__Connect__ {
-                    player_name: _,
-                    to_player_tx: _,
-                } => unreachable!(),
-                Action::__Disconnect__ => unreachable!(),
             }
         }
         action_move_execution(
             &mut self.map,
             &mut self.players,
+            turn,
             next_positions,
             moving_players,
             swap_players,
             &self.active_swapshrooms,
+            &self.ruleset,
         )
         .await;
+        for player in self.players.values_mut() {
+            player.request_id = None;
+        }
+
+        for (player_uuid, action) in player_actions {
+            eprintln!(
+                "{} Player `{}` tried to do Action `{:?}`, but they are not Connected to the Game!",
+                p, player_uuid, action
+            );
+        }
     }
 }
 
-fn action_move_collection(
+async fn action_move_collection(
     map: &Map,
     player: &mut Player,
+    turn: u32,
     player_uuid: Uuid,
     direction: Option<Direction>,
-    next_positions: &mut HashMap<Pos, Vec<Uuid>>,
+    next_positions: &mut BTreeMap<Pos, Vec<Uuid>>,
 ) {
-    let next_pos = player.pos.get_next_pos_on_map(direction, map.len() as i32);
+    let next_pos = match map.get_next_pos(&player.pos, direction) {
+        Some(next_pos) => next_pos,
+        None => {
+            // Stays in place, where the others can not move to
+            msg_to_player_with_game_content(
+                map,
+                player,
+                turn,
+                MsgToPlayer::BlockedBy(BlockedBy::Edge),
+            )
+            .await;
+            player.pos.clone()
+        }
+    };
     match next_positions.entry(next_pos) {
         Entry::Occupied(occupied_entry) => {
             occupied_entry.into_mut().push(player_uuid);
@@ -417,27 +1031,32 @@ fn action_move_collection(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn action_move_execution(
     map: &mut Map,
-    players: &mut HashMap<Uuid, Player>,
-    mut next_positions: HashMap<Pos, Vec<Uuid>>,
+    players: &mut BTreeMap<Uuid, Player>,
+    turn: u32,
+    mut next_positions: BTreeMap<Pos, Vec<Uuid>>,
     moving_players: Vec<Uuid>,
     swap_players: Vec<(Pos, Pos)>,
-    active_swapshrooms: &HashMap<u32, (Pos, Pos)>,
+    active_swapshrooms: &BTreeMap<u32, (Pos, Pos)>,
+    ruleset: &Ruleset,
 ) {
     for (player_uuid, player) in players.iter_mut() {
         if !moving_players.contains(player_uuid) {
             action_move_collection(
                 map,
                 player,
+                turn,
                 player_uuid.to_owned(),
                 None,
                 &mut next_positions,
-            );
+            )
+            .await;
         }
     }
 
-    let wallbushes = map.get_wallbushes();
+    let wallbushes = map.get_wallbushes(ruleset);
     let mut active_swapshroom_positions = HashSet::<Pos>::new();
     for (_, (p1, p2)) in active_swapshrooms.iter() {
         active_swapshroom_positions.insert(p1.to_owned());
@@ -452,6 +1071,7 @@ async fn action_move_execution(
                 msg_to_player_with_game_content(
                     map,
                     player,
+                    turn,
                     MsgToPlayer::BlockedBy(BlockedBy::WallBush),
                 )
                 .await;
@@ -480,6 +1100,7 @@ async fn action_move_execution(
                 msg_to_player_with_game_content(
                     map,
                     player,
+                    turn,
                     MsgToPlayer::BlockedBy(BlockedBy::Swapshroom),
                 )
                 .await;
@@ -487,7 +1108,7 @@ async fn action_move_execution(
             }
             // Can move
             player.pos = pos;
-            msg_to_player_with_game_content(map, player, MsgToPlayer::Moved).await;
+            msg_to_player_with_game_content(map, player, turn, MsgToPlayer::Moved).await;
         } else {
             // AnotherPlayer occupies or tried to occupie the same spot
             for uuid in uuids {
@@ -499,6 +1120,7 @@ async fn action_move_execution(
                 msg_to_player_with_game_content(
                     map,
                     player,
+                    turn,
                     MsgToPlayer::BlockedBy(BlockedBy::AnotherPlayer),
                 )
                 .await;
@@ -523,69 +1145,72 @@ async fn action_move_execution(
 async fn action_harvest(
     map: &mut Map,
     player: &mut Player,
-    active_swapshrooms: &mut HashMap<u32, (Pos, Pos)>,
+    turn: u32,
+    active_swapshrooms: &mut BTreeMap<u32, (Pos, Pos)>,
     swap_players: &mut Vec<(Pos, Pos)>,
+    ruleset: &Ruleset,
 ) {
     let mut cell = map.get_cell(&player.pos).clone();
     let msg_to_player = match cell.plant.clone() {
         Plant::None => MsgToPlayer::NoHarvest,
         Plant::Wheat(wheat) => {
-            if wheat.growth == Wheat::GROWTH_TO_GRAINS {
+            let rules = &ruleset.wheat;
+            if wheat.growth >= rules.growth_to_grains {
                 cell.plant = Plant::None;
-                player.harvest(
-                    Harvest::Grains,
-                    Wheat::GRAINS_YIELD as u32,
-                    Wheat::POINTS_PER_GRAINS as u32,
-                )
+                player.harvest(Harvest::Grains, rules.grains_yield, rules.points_per_grains)
             } else {
                 cell.plant = Plant::None;
                 MsgToPlayer::NoHarvest
             }
         }
         Plant::Bush(bush) => {
+            let rules = &ruleset.bush;
             if bush.berries > 0 {
                 cell.plant = Plant::Bush(Bush {
-                    growth: Bush::GROWTH_TO_WOOD,
+                    growth: rules.growth_to_wood,
                     berries: 0,
                 });
                 player.harvest(
                     Harvest::Berry,
                     bush.berries as u32,
-                    Bush::POINTS_PER_BERRIES,
+                    rules.points_per_berries,
                 )
-            } else if bush.growth >= Bush::GROWTH_TO_WOOD {
+            } else if bush.growth >= rules.growth_to_wood {
                 cell.plant = Plant::None;
-                player.harvest(Harvest::Wood, Bush::WOOD_YIELD, Bush::POINTS_PER_WOOD)
+                player.harvest(Harvest::Wood, rules.wood_yield, rules.points_per_wood)
             } else {
                 cell.plant = Plant::None;
                 MsgToPlayer::NoHarvest
             }
         }
         Plant::Tree(tree) => {
-            if tree.growth == Tree::GROWTH_TO_WOOD {
+            let rules = &ruleset.tree;
+            if tree.growth >= rules.growth_to_wood {
                 cell.plant = Plant::None;
-                player.harvest(Harvest::Wood, Tree::WOOD_YIELD, Tree::POINTS_PER_WOOD)
+                player.harvest(Harvest::Wood, rules.wood_yield, rules.points_per_wood)
             } else {
                 cell.plant = Plant::None;
                 MsgToPlayer::NoHarvest
             }
         }
         Plant::Cane(cane) => {
-            if cane.growth == Cane::GROWTH_TO_SUGAR {
+            let rules = &ruleset.cane;
+            if cane.growth >= rules.growth_to_sugar {
                 cell.plant = Plant::None;
-                player.harvest(Harvest::Sugar, Cane::SUGAR_YIELD, Cane::POINTS_PER_SUGAR)
+                player.harvest(Harvest::Sugar, rules.sugar_yield, rules.points_per_sugar)
             } else {
                 cell.plant = Plant::None;
                 MsgToPlayer::NoHarvest
             }
         }
         Plant::Pumpkin(pumpkin) => {
-            if pumpkin.growth >= Pumpkin::GROWTH_TO_PUMPKINSEED {
+            let rules = &ruleset.pumpkin;
+            if pumpkin.growth >= rules.growth_to_pumpkinseed {
                 cell.plant = Plant::None;
                 player.harvest(
                     Harvest::PumpkinSeed,
                     pumpkin.pumpkinseed_yield(),
-                    Pumpkin::POINTS_PER_PUMPKINSEED,
+                    rules.points_per_pumpkinseed,
                 )
             } else {
                 cell.plant = Plant::None;
@@ -593,12 +1218,13 @@ async fn action_harvest(
             }
         }
         Plant::Cactus(cactus) => {
-            if cactus.growth >= Cactus::GROWTH_PER_CACTUSMEAT {
+            let rules = &ruleset.cactus;
+            if cactus.growth >= rules.growth_per_cactusmeat {
                 cell.plant = Plant::None;
                 player.harvest(
                     Harvest::CactusMeat,
                     cactus.size as u32,
-                    Cactus::POINTS_PER_CACTUSMEAT,
+                    rules.points_per_cactusmeat,
                 )
             } else {
                 cell.plant = Plant::None;
@@ -632,20 +1258,17 @@ async fn action_harvest(
             }
         }
         Plant::Sunflower(sunflower) => {
-            if sunflower.growth == Sunflower::GROWTH_TO_POWER {
+            let rules = &ruleset.sunflower;
+            if sunflower.growth >= rules.growth_to_power {
                 let max_rank = map.get_highest_sunflower_rank();
                 if sunflower.rank == max_rank {
                     cell.plant = Plant::None;
-                    player.harvest(
-                        Harvest::Power,
-                        Sunflower::POWER_YIELD as u32,
-                        Sunflower::POINTS_PER_POWER,
-                    )
+                    player.harvest(Harvest::Power, rules.power_yield, rules.points_per_power)
                 } else {
                     cell.plant = Plant::None;
-                    player.points = player.points.saturating_sub(
-                        Sunflower::POINTS_PER_POWER * (Sunflower::POWER_YIELD as u32),
-                    );
+                    player.points = player
+                        .points
+                        .saturating_sub(rules.points_per_power * rules.power_yield);
                     MsgToPlayer::NoHarvest
                 }
             } else {
@@ -656,41 +1279,64 @@ async fn action_harvest(
     };
 
     map.set_cell(&player.pos, cell);
-    msg_to_player_with_game_content(map, player, msg_to_player).await;
+    msg_to_player_with_game_content(map, player, turn, msg_to_player).await;
 }
 
-async fn action_plant(map: &mut Map, player: &mut Player, seed: Seed, rng: &mut SmallRng) {
+async fn action_plant(
+    map: &mut Map,
+    player: &mut Player,
+    turn: u32,
+    seed: Seed,
+    rng: &mut Xoshiro256PlusPlus,
+    ruleset: &Ruleset,
+) {
     if let Some(volume) = player.seeds.get_mut(&seed) {
         if *volume == 0 {
-            return msg_to_player_with_game_content(map, player, MsgToPlayer::NotEnoughSeed).await;
+            return msg_to_player_with_game_content(map, player, turn, MsgToPlayer::NotEnoughSeed)
+                .await;
         }
         *volume -= 1;
         let mut cell = map.get_cell(&player.pos).to_owned();
 
         if let Plant::Wallbush(_) | Plant::Swapshroom(_) = cell.plant {
-            return msg_to_player_with_game_content(map, player, MsgToPlayer::CannotPlantOver)
-                .await;
+            return msg_to_player_with_game_content(
+                map,
+                player,
+                turn,
+                MsgToPlayer::CannotPlantOver,
+            )
+            .await;
         }
 
-        let plant = match (seed, cell.clone().ground) {
-            (Seed::Wheat, Ground::Dirt | Ground::Tiled) => Plant::Wheat(Wheat { growth: 0 }),
-            (Seed::Bush, Ground::Tiled) => Plant::Bush(Bush {
+        if !ruleset.grounds(&seed).contains(&cell.ground) {
+            return msg_to_player_with_game_content(
+                map,
+                player,
+                turn,
+                MsgToPlayer::WrongGroundType,
+            )
+            .await;
+        }
+
+        let plant = match seed {
+            Seed::Wheat => Plant::Wheat(Wheat { growth: 0 }),
+            Seed::Bush => Plant::Bush(Bush {
                 growth: 0,
                 berries: 0,
             }),
-            (Seed::Tree, Ground::Dirt) => Plant::Tree(Tree { growth: 0 }),
-            (Seed::Cane, Ground::Sand) => Plant::Cane(Cane { growth: 0 }),
-            (Seed::Pumpkin, Ground::Tiled) => Plant::Pumpkin(Pumpkin {
+            Seed::Tree => Plant::Tree(Tree { growth: 0 }),
+            Seed::Cane => Plant::Cane(Cane { growth: 0 }),
+            Seed::Pumpkin => Plant::Pumpkin(Pumpkin {
                 growth: 0,
                 current_size: 1,
                 max_size: 1,
             }),
-            (Seed::Cactus, Ground::Sand) => Plant::Cactus(Cactus { growth: 0, size: 0 }),
-            (Seed::Wallbush, Ground::Tiled) => Plant::Wallbush(Wallbush {
+            Seed::Cactus => Plant::Cactus(Cactus { growth: 0, size: 0 }),
+            Seed::Wallbush => Plant::Wallbush(Wallbush {
                 growth: 0,
-                health: Wallbush::MAX_HEALTH,
+                health: ruleset.wallbush.max_health,
             }),
-            (Seed::Swapshroom, _) => {
+            Seed::Swapshroom => {
                 let pair_id = match player.next_swapshroom_pair_id {
                     Some(pair_id) => {
                         player.next_swapshroom_pair_id = None;
@@ -708,7 +1354,7 @@ async fn action_plant(map: &mut Map, player: &mut Player, seed: Seed, rng: &mut
                     active: false,
                 })
             }
-            (Seed::Sunflower, Ground::Stone) => {
+            Seed::Sunflower => {
                 let stones = map.get_stones();
                 for pos in stones {
                     let mut cell = map.get_cell(&pos).to_owned();
@@ -726,53 +1372,46 @@ async fn action_plant(map: &mut Map, player: &mut Player, seed: Seed, rng: &mut
                         map.set_cell(&pos, cell);
                     }
                 }
-                return msg_to_player_with_game_content(map, player, MsgToPlayer::Planted).await;
-            }
-            (_seed, _ground) => {
-                return msg_to_player_with_game_content(map, player, MsgToPlayer::WrongGroundType)
+                return msg_to_player_with_game_content(map, player, turn, MsgToPlayer::Planted)
                     .await;
             }
         };
         cell.plant = plant;
         map.set_cell(&player.pos, cell);
-        return msg_to_player_with_game_content(map, player, MsgToPlayer::Planted).await;
+        return msg_to_player_with_game_content(map, player, turn, MsgToPlayer::Planted).await;
     }
-    msg_to_player_with_game_content(map, player, MsgToPlayer::NotEnoughSeed).await;
+    msg_to_player_with_game_content(map, player, turn, MsgToPlayer::NotEnoughSeed).await;
 }
 
-async fn action_trade(map: &mut Map, player: &mut Player, seed: Seed, volume: u32) {
+async fn action_trade(
+    map: &mut Map,
+    player: &mut Player,
+    turn: u32,
+    seed: Seed,
+    volume: u32,
+    ruleset: &Ruleset,
+) {
     if volume == 0 {
-        return msg_to_player_with_game_content(map, player, MsgToPlayer::InvalidTrade).await;
-    }
-
-    let trade = match seed {
-        Seed::Wheat => {
-            return msg_to_player_with_game_content(map, player, MsgToPlayer::InvalidTrade).await
-        }
-        Seed::Bush => vec![(Harvest::Grains, Seed::TRADE_GRAINS_FOR_BUSH)],
-        Seed::Tree => vec![(Harvest::Wood, Seed::TRADE_WOOD_FOR_TREE)],
-        Seed::Cane => vec![(Harvest::Grains, Seed::TRADE_GRAINS_FOR_CANE)],
-        Seed::Pumpkin => vec![
-            (Harvest::Berry, Seed::TRADE_BERRIES_FOR_PUMPKIN),
-            (Harvest::Wood, Seed::TRADE_WOOD_FOR_PUMPKIN),
-        ],
-        Seed::Cactus => vec![
-            (Harvest::Sugar, Seed::TRADE_SUGAR_FOR_CACTUS),
-            (Harvest::Wood, Seed::TRADE_WOOD_FOR_CACTUS),
-        ],
-        Seed::Wallbush => vec![(Harvest::PumpkinSeed, Seed::TRADE_PUMKINSEED_FOR_WALLBUSH)],
-        Seed::Swapshroom => vec![(Harvest::CactusMeat, Seed::TRADE_CACTUSMEAT_FOR_SWAPSHROOM)],
-        Seed::Sunflower => vec![
-            (Harvest::PumpkinSeed, Seed::TRADE_PUMKINSEED_FOR_SUNFLOWER),
-            (Harvest::CactusMeat, Seed::TRADE_CACTUSMEAT_FOR_SUNFLOWER),
-        ],
+        return msg_to_player_with_game_content(map, player, turn, MsgToPlayer::InvalidTrade).await;
+    }
+
+    let trade = match ruleset.trades.get(&seed) {
+        Some(trade) => trade
+            .iter()
+            .map(|(harvest, cost)| (harvest.clone(), *cost))
+            .collect(),
+        None => {
+            return msg_to_player_with_game_content(map, player, turn, MsgToPlayer::InvalidTrade)
+                .await
+        }
     };
-    action_trade_helper(map, player, volume, seed, trade).await;
+    action_trade_helper(map, player, turn, volume, seed, trade).await;
 }
 
 async fn action_trade_helper(
     map: &mut Map,
     player: &mut Player,
+    turn: u32,
     volume: u32,
     seed: Seed,
     trade: Vec<(Harvest, u32)>,
@@ -795,20 +1434,20 @@ async fn action_trade_helper(
             }
         }
         match player.seeds.entry(seed.clone()) {
-            Entry::Occupied(occupied_entry) => {
+            hash_map::Entry::Occupied(occupied_entry) => {
                 *occupied_entry.into_mut() += volume;
             }
-            Entry::Vacant(vacant_entry) => {
+            hash_map::Entry::Vacant(vacant_entry) => {
                 vacant_entry.insert(volume);
             }
         }
-        msg_to_player_with_game_content(map, player, MsgToPlayer::Traded).await;
+        msg_to_player_with_game_content(map, player, turn, MsgToPlayer::Traded).await;
     } else {
-        msg_to_player_with_game_content(map, player, MsgToPlayer::NotEnoughHarvest).await;
+        msg_to_player_with_game_content(map, player, turn, MsgToPlayer::NotEnoughHarvest).await;
     }
 }
 
-async fn action_till(map: &mut Map, player: &mut Player) {
+async fn action_till(map: &mut Map, player: &mut Player, turn: u32) {
     let cell = map.get_cell(&player.pos).to_owned();
     match (cell.ground, cell.plant) {
         (Ground::Dirt, Plant::Swapshroom(swapshroom)) => map.set_cell(
@@ -844,15 +1483,60 @@ async fn action_till(map: &mut Map, player: &mut Player) {
             );
         }
         _ => {
-            return msg_to_player_with_game_content(map, player, MsgToPlayer::WrongGroundType)
-                .await;
+            return msg_to_player_with_game_content(
+                map,
+                player,
+                turn,
+                MsgToPlayer::WrongGroundType,
+            )
+            .await;
         }
     }
-    msg_to_player_with_game_content(map, player, MsgToPlayer::Tilled).await;
+    msg_to_player_with_game_content(map, player, turn, MsgToPlayer::Tilled).await;
 }
 
-async fn msg_to_player_with_game_content(map: &Map, player: &mut Player, result: MsgToPlayer) {
+async fn action_look(
+    map: &Map,
+    player: &mut Player,
+    turn: u32,
+    player_uuid: Uuid,
+    player_positions: &[(Uuid, String, Pos)],
+    radius: i32,
+) {
+    let cells = (-radius..=radius)
+        .map(|dy| {
+            (-radius..=radius)
+                .map(|dx| {
+                    map.get_offset_pos(&player.pos, &Pos { x: dx, y: dy })
+                        .map(|pos| map.get_cell(&pos).to_owned())
+                })
+                .collect()
+        })
+        .collect();
+    let players = player_positions
+        .iter()
+        .filter(|(other_uuid, _, _)| *other_uuid != player_uuid)
+        .filter_map(|(_, player_name, pos)| {
+            let offset = map.get_offset_to(&player.pos, pos);
+            (offset.x.abs() <= radius && offset.y.abs() <= radius).then(|| VisiblePlayer {
+                player_name: player_name.clone(),
+                offset,
+            })
+        })
+        .collect();
+    msg_to_player_with_game_content(map, player, turn, MsgToPlayer::Looked { cells, players })
+        .await;
+}
+
+async fn msg_to_player_with_game_content(
+    map: &Map,
+    player: &mut Player,
+    turn: u32,
+    result: MsgToPlayer,
+) {
     let msg = MsgToPlayerWithGameContent {
+        turn,
+        id: player.request_id,
         result,
         cell: map.get_cell(&player.pos).to_owned(),
         harvests: player.harvests.clone(),
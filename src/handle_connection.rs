@@ -1,25 +1,36 @@
+use std::sync::Arc;
+
 use futures::StreamExt;
-use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tokio::{
-    net::TcpStream,
+    io::{AsyncRead, AsyncWrite},
     sync::mpsc::{self, Sender},
 };
 use tokio_util::codec::{Framed, LinesCodec};
 use uuid::Uuid;
 
 use crate::{
-    game::{Action, Game, GameSettings},
-    send_to_player::{send_msg_to_player, send_to_player},
-    server::Games,
+    config::{named_file, ServerConfig},
+    game::{Action, GameMsg, GameStatus},
+    send_to_player::{send_reply_to_player, send_to_player},
+    server::{create_game, Games},
 };
 
-pub async fn handle_connection(framed: Framed<TcpStream, LinesCodec>, games: Games) {
+pub use farmio_protocol::lobby::{LobbyMsg, LobbyToPlayer, NewGameSettings};
+
+/// Speaks the JSON line protocol over any transport, TCP or the stdin/stdout of a BOT
+pub async fn handle_connection<T: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+    framed: Framed<T, LinesCodec>,
+    games: Games,
+    config: Arc<ServerConfig>,
+) {
     println!("Player connecting...");
 
     // State
     let mut s_player_name: Option<String> = None;
     let mut s_player_uuid: Option<Uuid> = None;
     let mut s_game_name: Option<String> = None;
+    let connection_id = Uuid::new_v4();
 
     // Com
     let (tcp_tx, mut tcp_rx) = framed.split();
@@ -29,8 +40,10 @@ pub async fn handle_connection(framed: Framed<TcpStream, LinesCodec>, games: Gam
     tokio::spawn(async move { send_to_player(to_player_rx, tcp_tx).await });
 
     while let Some(Ok(msg)) = tcp_rx.next().await {
+        let (request_id, value) = split_request_id(&msg);
+
         // Lobby
-        if let Ok(lobby_msg) = serde_json::from_str::<LobbyMsg>(&msg) {
+        if let Ok(lobby_msg) = serde_json::from_value::<LobbyMsg>(value.clone()) {
             match lobby_msg {
                 LobbyMsg::NewGame {
                     player_name,
@@ -43,42 +56,146 @@ pub async fn handle_connection(framed: Framed<TcpStream, LinesCodec>, games: Gam
                     s_player_uuid = Some(player_uuid);
                     s_game_name = Some(game_name.clone());
 
+                    // The files of the Game are named after it in the `output_dir`
+                    if let Err(err) = named_file(&config.output_dir, &game_name, "replay") {
+                        eprintln!("Player `{}` tried to create a Game: `{}`", player_name, err);
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::InvalidGameName,
+                        )
+                        .await;
+                        continue;
+                    }
+
                     // Check if Game exists
-                    if games.contains_key(&game_name) {
+                    if games.get(&game_name).is_some_and(|e| !e.is_finished()) {
                         eprintln!(
                             "Player `{}` tried to create Game `{}` witch already exists!",
                             player_name, game_name
                         );
-                        send_msg_to_player(&mut to_player_tx, LobbyToPlayer::GameAlreadyExists)
-                            .await;
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::GameAlreadyExists,
+                        )
+                        .await;
                         continue;
                     }
 
-                    // Com
-                    let (to_game_tx, to_game_rx) = mpsc::channel::<PlayerAction>(1024);
-                    s_to_game_tx = Some(to_game_tx.clone());
+                    // Check if the Server can host another Game
+                    let running_games = games.iter().filter(|e| !e.is_finished()).count();
+                    if running_games >= config.max_games {
+                        eprintln!(
+                            "Player `{}` tried to create Game `{}`, but the Server already hosts `{}` Games!",
+                            player_name, game_name, config.max_games
+                        );
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::TooManyGames,
+                        )
+                        .await;
+                        continue;
+                    }
 
                     // New Game
+                    let game_settings = game_settings
+                        .map(NewGameSettings::into_game_settings)
+                        .unwrap_or_else(|| config.default_game_settings.clone());
+                    if let Err(err) = game_settings.validate() {
+                        eprintln!(
+                            "Player `{}` tried to create Game `{}` with invalid settings: {}",
+                            player_name, game_name, err
+                        );
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::InvalidGameSettings(err),
+                        )
+                        .await;
+                        continue;
+                    }
+
+                    // Only the BOTs configured on the Server can be started
+                    if let Some(bot_name) = game_settings
+                        .bots
+                        .iter()
+                        .find(|bot_name| !config.bots.contains_key(*bot_name))
+                    {
+                        eprintln!(
+                            "Player `{}` tried to create Game `{}` with unknown BOT `{}`!",
+                            player_name, game_name, bot_name
+                        );
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::UnknownBot(bot_name.clone()),
+                        )
+                        .await;
+                        continue;
+                    }
+
                     println!(
                         "New Game by Player `{}`: `{}({:?})`",
                         &player_name, &game_name, &game_settings
                     );
-                    let mut game = Game::new(game_name.clone(), to_game_rx, game_settings).await;
-                    games.insert(game_name.clone(), to_game_tx.clone());
-                    tokio::spawn(async move { game.run().await });
-                    send_msg_to_player(&mut to_player_tx, LobbyToPlayer::GameCreated).await;
+                    let ruleset = match config.load_ruleset(&game_settings) {
+                        Ok(ruleset) => ruleset,
+                        Err(err) => {
+                            eprintln!(
+                                "Player `{}` tried to create Game `{}` with an invalid ruleset: {}",
+                                player_name, game_name, err
+                            );
+                            send_lobby_reply(
+                                &mut to_player_tx,
+                                request_id,
+                                LobbyToPlayer::InvalidRuleset,
+                            )
+                            .await;
+                            continue;
+                        }
+                    };
+
+                    let map = match config.load_map(&game_settings, &ruleset) {
+                        Ok(map) => map,
+                        Err(err) => {
+                            eprintln!(
+                                "Player `{}` tried to create Game `{}` with an invalid map: {}",
+                                player_name, game_name, err
+                            );
+                            send_lobby_reply(
+                                &mut to_player_tx,
+                                request_id,
+                                LobbyToPlayer::InvalidMap,
+                            )
+                            .await;
+                            continue;
+                        }
+                    };
+
+                    let password = game_settings.password.clone();
+                    let (to_game_tx, _game_handle) =
+                        create_game(&games, &config, &game_name, game_settings, ruleset, map).await;
+                    s_to_game_tx = Some(to_game_tx.clone());
+                    send_lobby_reply(&mut to_player_tx, request_id, LobbyToPlayer::GameCreated)
+                        .await;
 
                     // Connect
                     send_msg_to_game(
                         &mut Some(to_game_tx),
-                        Action::__Connect__ {
+                        GameMsg::Connect {
                             player_name,
                             to_player_tx: to_player_tx.clone(),
+                            token: None,
+                            password,
                         },
                         &s_player_uuid,
                         &s_player_name,
                         &s_game_name,
                         &mut to_player_tx,
+                        connection_id,
+                        request_id,
                     )
                     .await;
                 }
@@ -86,6 +203,8 @@ pub async fn handle_connection(framed: Framed<TcpStream, LinesCodec>, games: Gam
                     player_name,
                     player_uuid,
                     game_name,
+                    token,
+                    password,
                 } => {
                     // State
                     s_player_name = Some(player_name.clone());
@@ -93,21 +212,25 @@ pub async fn handle_connection(framed: Framed<TcpStream, LinesCodec>, games: Gam
                     s_game_name = Some(game_name.clone());
 
                     // Connect Player to the Game
-                    if let Some(to_game_tx) = games.get(&game_name).map(|e| e.to_owned()) {
+                    if let Some(to_game_tx) = games.get(&game_name).map(|e| e.to_game_tx.clone()) {
                         // Com
                         s_to_game_tx = Some(to_game_tx.clone());
 
                         // Connect
                         send_msg_to_game(
                             &mut Some(to_game_tx),
-                            Action::__Connect__ {
+                            GameMsg::Connect {
                                 player_name,
                                 to_player_tx: to_player_tx.clone(),
+                                token,
+                                password,
                             },
                             &s_player_uuid,
                             &s_player_name,
                             &s_game_name,
                             &mut to_player_tx,
+                            connection_id,
+                            request_id,
                         )
                         .await;
                     } else {
@@ -115,22 +238,144 @@ pub async fn handle_connection(framed: Framed<TcpStream, LinesCodec>, games: Gam
                             "Player `{}` tried to join nonexistent Game `{}` ",
                             player_name, game_name
                         );
-                        send_msg_to_player(&mut to_player_tx, LobbyToPlayer::GameNotExists).await;
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::GameNotExists,
+                        )
+                        .await;
                     };
                 }
+                LobbyMsg::ListGames => {
+                    let mut game_statuses = games
+                        .iter()
+                        .map(|e| e.status.borrow().clone())
+                        .collect::<Vec<GameStatus>>();
+                    game_statuses.sort_by(|a, b| a.game_name.cmp(&b.game_name));
+                    send_lobby_reply(
+                        &mut to_player_tx,
+                        request_id,
+                        LobbyToPlayer::GameList(game_statuses),
+                    )
+                    .await;
+                }
+                LobbyMsg::GameInfo { game_name } => {
+                    match games.get(&game_name).map(|e| e.status.borrow().clone()) {
+                        Some(game_status) => {
+                            send_lobby_reply(
+                                &mut to_player_tx,
+                                request_id,
+                                LobbyToPlayer::GameInfo(Box::new(game_status)),
+                            )
+                            .await
+                        }
+                        None => {
+                            send_lobby_reply(
+                                &mut to_player_tx,
+                                request_id,
+                                LobbyToPlayer::GameNotExists,
+                            )
+                            .await
+                        }
+                    }
+                }
+                LobbyMsg::Spectate {
+                    game_name,
+                    admin_token,
+                } => {
+                    let entry = games.get(&game_name).map(|e| {
+                        let public = e.status.borrow().game_settings.public_spectating;
+                        (e.to_game_tx.clone(), public)
+                    });
+                    if let Some((to_game_tx, public)) = entry {
+                        let admin =
+                            config.admin_token.is_some() && config.admin_token == admin_token;
+                        if !public && !admin {
+                            eprintln!("Unauthorized attempt to spectate Game `{}`", game_name);
+                            send_lobby_reply(
+                                &mut to_player_tx,
+                                request_id,
+                                LobbyToPlayer::NotAuthorized,
+                            )
+                            .await;
+                            continue;
+                        }
+                        // Spectators are not connected as Players, their Actions are rejected
+                        send_msg_to_game(
+                            &mut Some(to_game_tx),
+                            GameMsg::Spectate {
+                                to_spectator_tx: to_player_tx.clone(),
+                            },
+                            &Some(Uuid::nil()),
+                            &s_player_name,
+                            &Some(game_name),
+                            &mut to_player_tx,
+                            connection_id,
+                            request_id,
+                        )
+                        .await;
+                    } else {
+                        eprintln!("Spectator tried to watch nonexistent Game `{}` ", game_name);
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::GameNotExists,
+                        )
+                        .await;
+                    }
+                }
+                LobbyMsg::SaveGameState {
+                    game_name,
+                    admin_token,
+                } => {
+                    if config.admin_token.is_none() || config.admin_token != Some(admin_token) {
+                        eprintln!("Unauthorized attempt to save Game `{}`", game_name);
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::NotAuthorized,
+                        )
+                        .await;
+                        continue;
+                    }
+                    if let Some(to_game_tx) = games.get(&game_name).map(|e| e.to_game_tx.clone()) {
+                        send_msg_to_game(
+                            &mut Some(to_game_tx),
+                            GameMsg::Save {
+                                to_player_tx: to_player_tx.clone(),
+                            },
+                            &Some(Uuid::nil()),
+                            &s_player_name,
+                            &Some(game_name),
+                            &mut to_player_tx,
+                            connection_id,
+                            request_id,
+                        )
+                        .await;
+                    } else {
+                        send_lobby_reply(
+                            &mut to_player_tx,
+                            request_id,
+                            LobbyToPlayer::GameNotExists,
+                        )
+                        .await;
+                    }
+                }
             }
             continue;
         }
 
         // Action
-        if let Ok(action) = serde_json::from_str::<Action>(&msg) {
+        if let Ok(action) = serde_json::from_value::<Action>(value) {
             send_msg_to_game(
                 &mut s_to_game_tx,
-                action,
+                GameMsg::Action(action),
                 &s_player_uuid,
                 &s_player_name,
                 &s_game_name,
                 &mut to_player_tx,
+                connection_id,
+                request_id,
             )
             .await;
             continue;
@@ -140,74 +385,90 @@ pub async fn handle_connection(framed: Framed<TcpStream, LinesCodec>, games: Gam
             "Invalid Msg `{}` by Player `{:?}` playing Game `{:?}` ",
             msg, s_player_name, s_game_name
         );
-        send_msg_to_player(&mut to_player_tx, LobbyToPlayer::InvalidMsg).await;
+        send_lobby_reply(&mut to_player_tx, request_id, LobbyToPlayer::InvalidMsg).await;
     }
 
     println!("Player `{:?}` disconnecting...", s_player_name);
     send_msg_to_game(
         &mut s_to_game_tx,
-        Action::__Disconnect__,
+        GameMsg::Disconnect,
         &s_player_uuid,
         &s_player_name,
         &s_game_name,
         &mut to_player_tx,
+        connection_id,
+        None,
     )
     .await
 }
 
-#[derive(Debug, Deserialize)]
-enum LobbyMsg {
-    NewGame {
-        player_name: String,
-        player_uuid: Uuid,
-        game_name: String,
-        game_settings: GameSettings,
-    },
-    JoinGame {
-        player_name: String,
-        player_uuid: Uuid,
-        game_name: String,
-    },
+#[derive(Debug)]
+pub struct PlayerAction {
+    pub player_uuid: Uuid,
+    /// Distinguishes the connections using the same `player_uuid`
+    pub connection_id: Uuid,
+    /// The Action of the Player, or a request of the Server
+    pub action: GameMsg,
+    /// Echoed back in the result of the Action
+    pub request_id: Option<u64>,
 }
 
-#[derive(Debug, Serialize)]
-enum LobbyToPlayer {
-    GameCreated,
-    GameAlreadyExists,
-    GameNotExists,
-    NotConnectedToAnyGame,
-    UnableToCommunicateWithGame,
-    InvalidMsg,
+/// A request may carry an `id` next to its only key, it is echoed back in the reply:
+/// `{ "id": 7, "Move": { "direction": "Up" } }`, or `{ "id": 8, "Harvest": null }`
+fn split_request_id(msg: &str) -> (Option<u64>, Value) {
+    let mut value = serde_json::from_str::<Value>(msg).unwrap_or(Value::Null);
+    let request_id = match &mut value {
+        Value::Object(object) => object.remove("id").and_then(|id| id.as_u64()),
+        _ => None,
+    };
+    (request_id, value)
 }
 
-#[derive(Debug)]
-pub struct PlayerAction {
-    pub player_uuid: Uuid,
-    pub action: Action,
+async fn send_lobby_reply(
+    to_player_tx: &mut Sender<String>,
+    request_id: Option<u64>,
+    lobby_to_player: LobbyToPlayer,
+) {
+    send_reply_to_player(to_player_tx, request_id, lobby_to_player).await;
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn send_msg_to_game(
     s_to_game_tx: &mut Option<Sender<PlayerAction>>,
-    action: Action,
+    action: GameMsg,
     s_player_uuid: &Option<Uuid>,
     s_player_name: &Option<String>,
     s_game_name: &Option<String>,
     to_player_tx: &mut Sender<String>,
+    connection_id: Uuid,
+    request_id: Option<u64>,
 ) {
     if let (Some(to_game_tx), Some(player_uuid)) = (s_to_game_tx, s_player_uuid) {
         let player_action = PlayerAction {
             action,
             player_uuid: player_uuid.to_owned(),
+            connection_id,
+            request_id,
         };
         if let Err(err) = to_game_tx.send(player_action).await {
             eprintln!(
                 "Unable to send Action of Player `{:?}` to Game `{:?}`: `{}`",
                 s_player_name, s_game_name, err
             );
-            send_msg_to_player(to_player_tx, LobbyToPlayer::UnableToCommunicateWithGame).await;
+            send_lobby_reply(
+                to_player_tx,
+                request_id,
+                LobbyToPlayer::UnableToCommunicateWithGame,
+            )
+            .await;
         }
     } else {
         eprintln!("Player is not connected to any Game!");
-        send_msg_to_player(to_player_tx, LobbyToPlayer::NotConnectedToAnyGame).await;
+        send_lobby_reply(
+            to_player_tx,
+            request_id,
+            LobbyToPlayer::NotConnectedToAnyGame,
+        )
+        .await;
     }
 }
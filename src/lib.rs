@@ -0,0 +1,23 @@
+pub use farmio_protocol::{cell, direction, ground, harvest, plant, pos, seed};
+
+pub mod config;
+pub mod drawer;
+pub mod game;
+pub mod handle_connection;
+pub mod map;
+pub mod map_file;
+pub mod map_generator;
+pub mod player;
+pub mod render;
+pub mod replay;
+pub mod ruleset;
+pub mod save;
+pub mod send_to_player;
+pub mod server;
+pub mod simulator;
+pub mod snapshot;
+pub mod spectator;
+pub mod stdio_bot;
+pub mod tournament;
+pub mod viewer;
+pub mod websocket;
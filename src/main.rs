@@ -1,22 +1,182 @@
-mod cell;
-mod direction;
-mod drawer;
-mod game;
-mod ground;
-mod handle_connection;
-mod harvest;
-mod map;
-mod plant;
-mod player;
-mod pos;
-mod seed;
-mod send_to_player;
-mod server;
+use std::path::PathBuf;
 
-use crate::server::start_server;
+use clap::{Parser, Subcommand};
+
+use farmio::{
+    config::ServerConfig,
+    render::RenderFormat,
+    replay::{replay_game, ReplayRender},
+    server::start_server,
+    tournament::{run_tournament, TournamentConfig},
+};
+
+/// Farming BOT programming game server
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// JSON config file, the other options override its values
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+    /// Address to bind to
+    #[arg(long)]
+    ip: Option<String>,
+    /// Port to listen on
+    #[arg(short, long)]
+    port: Option<u16>,
+    /// Port to listen on for WebSockets, carrying the same JSON messages
+    #[arg(long)]
+    websocket_port: Option<u16>,
+    /// Port to serve the viewer of the Games on, over HTTP
+    #[arg(long)]
+    http_port: Option<u16>,
+    /// Maximum number of concurrently running Games
+    #[arg(long)]
+    max_games: Option<usize>,
+    /// Directory of the `.farmio` files
+    #[arg(short, long)]
+    output_dir: Option<PathBuf>,
+    /// Default number of players, if NewGame has no game_settings
+    #[arg(long)]
+    number_of_players: Option<u32>,
+    /// Default turn duration, if NewGame has no game_settings
+    #[arg(long)]
+    turn_duration_ms: Option<u32>,
+    /// Default map size, if NewGame has no game_settings
+    #[arg(long)]
+    map_size: Option<u32>,
+    /// Default seed, if NewGame has no game_settings
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Token required by the admin commands (e.g. SaveGameState)
+    #[arg(long)]
+    admin_token: Option<String>,
+    /// Directory of the ruleset files selectable by the Games
+    #[arg(long)]
+    rulesets_dir: Option<PathBuf>,
+    /// Directory of the map files selectable by the Games
+    #[arg(long)]
+    maps_dir: Option<PathBuf>,
+    /// Restore a Game from its `.save` file, can be repeated
+    #[arg(long)]
+    restore: Vec<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Re-simulate a recorded Game from its `.replay` file
+    Replay {
+        replay_file: PathBuf,
+        /// Draw every turn into `<output_dir>/<game_name>.replay.farmio`
+        #[arg(long)]
+        draw: bool,
+        /// Wait between the turns, to follow the drawing
+        #[arg(long, default_value_t = 0)]
+        turn_delay_ms: u64,
+        /// Write the JSON snapshot of every turn into `<output_dir>/<game_name>.replay.snapshots`
+        #[arg(long)]
+        snapshots: bool,
+        /// Render every turn into `<output_dir>/<game_name>.replay.frames/<turn>.svg` or `.png`,
+        /// or the whole Game into `<output_dir>/<game_name>.replay.gif`
+        #[arg(long, value_enum)]
+        render: Option<RenderFormat>,
+        /// Pixels per Cell of the rendered images
+        #[arg(long, default_value_t = 48)]
+        cell_size: u32,
+        /// Time each turn is shown in the rendered GIF
+        #[arg(long, default_value_t = 250)]
+        frame_delay_ms: u64,
+    },
+    /// Play a tournament between BOT programs and update their ratings
+    Tournament {
+        /// JSON file with the roster of BOTs, the format and the Game settings
+        tournament_file: PathBuf,
+    },
+}
+
+impl Cli {
+    fn into_server_config(self) -> (ServerConfig, Option<Command>) {
+        let mut config = match &self.config {
+            Some(path) => ServerConfig::load(path),
+            None => ServerConfig::default(),
+        };
+        if let Some(ip) = self.ip {
+            config.ip = ip;
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(websocket_port) = self.websocket_port {
+            config.websocket_port = Some(websocket_port);
+        }
+        if let Some(http_port) = self.http_port {
+            config.http_port = Some(http_port);
+        }
+        if let Some(max_games) = self.max_games {
+            config.max_games = max_games;
+        }
+        if let Some(output_dir) = self.output_dir {
+            config.output_dir = output_dir;
+        }
+        if let Some(admin_token) = self.admin_token {
+            config.admin_token = Some(admin_token);
+        }
+        if let Some(rulesets_dir) = self.rulesets_dir {
+            config.rulesets_dir = rulesets_dir;
+        }
+        if let Some(maps_dir) = self.maps_dir {
+            config.maps_dir = maps_dir;
+        }
+        config.restore.extend(self.restore);
+        let game_settings = &mut config.default_game_settings;
+        if let Some(number_of_players) = self.number_of_players {
+            game_settings.number_of_players = number_of_players;
+        }
+        if let Some(turn_duration_ms) = self.turn_duration_ms {
+            game_settings.turn_duration_ms = turn_duration_ms;
+        }
+        if let Some(map_size) = self.map_size {
+            game_settings.map_size = map_size;
+        }
+        if let Some(seed) = self.seed {
+            game_settings.seed = seed;
+        }
+        (config, self.command)
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    let ip_port = "127.0.0.1:5942";
-    start_server(ip_port).await;
+    let (config, command) = Cli::parse().into_server_config();
+    match command {
+        None => start_server(config).await,
+        Some(Command::Replay {
+            replay_file,
+            draw,
+            turn_delay_ms,
+            snapshots,
+            render,
+            cell_size,
+            frame_delay_ms,
+        }) => {
+            let render = render.map(|format| ReplayRender {
+                format,
+                cell_size,
+                frame_delay_ms,
+            });
+            replay_game(
+                replay_file,
+                draw,
+                turn_delay_ms,
+                snapshots,
+                render,
+                &config.output_dir,
+            )
+            .await
+        }
+        Some(Command::Tournament { tournament_file }) => {
+            run_tournament(config, TournamentConfig::load(&tournament_file)).await
+        }
+    }
 }
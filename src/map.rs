@@ -1,22 +1,30 @@
 use std::{
     cmp::Ordering,
-    collections::{hash_map::Entry, HashMap, HashSet},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
 };
 
-use rand::{rngs::SmallRng, seq::SliceRandom};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     cell::Cell,
     direction::Direction,
     drawer::Drawer,
+    game::GameSettings,
     ground::Ground,
-    plant::{Bush, Cactus, Cane, Plant, Pumpkin, Sunflower, Swapshroom, Tree, Wallbush, Wheat},
+    map_generator,
+    plant::{Bush, Cane, Plant, Sunflower, Wheat},
     pos::Pos,
+    ruleset::Ruleset,
 };
 
-#[derive(Clone)]
+pub use farmio_protocol::topology::Topology;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Map {
     map: Vec<Vec<Cell>>,
+    #[serde(default)]
+    topology: Topology,
 }
 
 impl Map {
@@ -25,9 +33,20 @@ impl Map {
     const GTP_SAND_CANE: u8 = 5;
     const GTP_WATER: u8 = 10;
 
-    pub fn generate_map(map_size: usize, rng: &mut SmallRng, number_of_player: u32) -> Map {
-        let a = map_size * map_size;
-        let stone: usize = number_of_player as usize;
+    pub fn generate_map(
+        game_settings: &GameSettings,
+        rng: &mut Xoshiro256PlusPlus,
+        ruleset: &Ruleset,
+    ) -> Map {
+        let (width, height) = game_settings.map_dimensions();
+        let (width, height) = (width as usize, height as usize);
+        let number_of_player = game_settings.number_of_players;
+        let a = width * height;
+        // The Stones of a symmetric map are placed after its rotation
+        let stone: usize = match game_settings.symmetric_map {
+            true => 0,
+            false => number_of_player as usize,
+        };
         let tilled_bush: usize = (a * Map::GTP_TILLED_BUSH as usize) / 100 + stone;
         let sand_empty: usize = (a * Map::GTP_SAND_EMPTY as usize) / 100 + tilled_bush;
         let sand_cane: usize = (a * Map::GTP_SAND_CANE as usize) / 100 + sand_empty;
@@ -44,8 +63,9 @@ impl Map {
                 Cell {
                     ground: Ground::Tiled,
                     plant: Plant::Bush(Bush {
-                        growth: Bush::GROWTH_TO_WOOD + Bush::GROWTH_PER_BERRIES * Bush::MAX_BERRIES,
-                        berries: Bush::MAX_BERRIES,
+                        growth: ruleset.bush.growth_to_wood
+                            + ruleset.bush.growth_per_berries * ruleset.bush.max_berries,
+                        berries: ruleset.bush.max_berries,
                     }),
                 }
             } else if sand_empty > i {
@@ -57,7 +77,7 @@ impl Map {
                 Cell {
                     ground: Ground::Sand,
                     plant: Plant::Cane(Cane {
-                        growth: Cane::GROWTH_TO_SUGAR,
+                        growth: ruleset.cane.growth_to_sugar,
                     }),
                 }
             } else if water > i {
@@ -69,32 +89,86 @@ impl Map {
                 Cell {
                     ground: Ground::Dirt,
                     plant: Plant::Wheat(Wheat {
-                        growth: Wheat::GROWTH_TO_GRAINS,
+                        growth: ruleset.wheat.growth_to_grains,
                     }),
                 }
             };
             flat_map.push(cell);
         }
-        // Shuffle
-        flat_map.shuffle(rng);
+        // Save as width*height map
+        let symmetric_players = game_settings.symmetric_map.then_some(number_of_player);
+        let topology = game_settings.topology.clone();
+        let map = map_generator::arrange(
+            &game_settings.map_generator,
+            flat_map,
+            (width, height),
+            symmetric_players,
+            &topology,
+            rng,
+        );
+
+        Self { map, topology }
+    }
+
+    pub fn from_cells(map: Vec<Vec<Cell>>, topology: Topology) -> Self {
+        Self { map, topology }
+    }
+
+    pub fn width(&self) -> usize {
+        self.map.first().map_or(0, |line| line.len())
+    }
 
-        // Save as n*n map
-        let mut map = Vec::with_capacity(map_size);
-        let mut i = 0;
-        for _y in 0..map_size {
-            let mut line = Vec::with_capacity(map_size);
-            for _x in 0..map_size {
-                line.push(flat_map[i].clone());
-                i += 1;
+    pub fn height(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    /// The Cell next to `pos`, `None` beyond the edge of a bounded map
+    pub fn get_next_pos(&self, pos: &Pos, direction: Option<Direction>) -> Option<Pos> {
+        let offset = direction.map_or(Pos { x: 0, y: 0 }, |direction| direction.to_pos());
+        self.get_offset_pos(pos, &offset)
+    }
+
+    /// `None` beyond the edge of a bounded map
+    pub fn get_offset_pos(&self, pos: &Pos, offset: &Pos) -> Option<Pos> {
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        match self.topology {
+            Topology::Torus => Some(pos.get_offset_pos_on_map(offset, width, height)),
+            Topology::Bounded => {
+                let pos = Pos {
+                    x: pos.x + offset.x,
+                    y: pos.y + offset.y,
+                };
+                (0..width)
+                    .contains(&pos.x)
+                    .then_some(pos)
+                    .filter(|pos| (0..height).contains(&pos.y))
             }
-            map.push(line);
         }
+    }
 
-        Self { map }
+    /// Shortest offset from `from` to `to`
+    pub fn get_offset_to(&self, from: &Pos, to: &Pos) -> Pos {
+        match self.topology {
+            Topology::Torus => {
+                from.get_offset_to_on_map(to, self.width() as i32, self.height() as i32)
+            }
+            Topology::Bounded => Pos {
+                x: to.x - from.x,
+                y: to.y - from.y,
+            },
+        }
     }
 
-    pub fn len(&self) -> usize {
-        self.map.len()
+    pub fn cells(&self) -> &Vec<Vec<Cell>> {
+        &self.map
     }
 
     pub fn get_cell(&self, pos: &Pos) -> &Cell {
@@ -117,12 +191,12 @@ impl Map {
         eprintln!("(set_cell) Invalid Position: `{:?}`", pos);
     }
 
-    pub fn get_wallbushes(&self) -> HashSet<Pos> {
+    pub fn get_wallbushes(&self, ruleset: &Ruleset) -> HashSet<Pos> {
         let mut wallbushes = HashSet::new();
         for (y, line) in self.map.iter().enumerate() {
             for (x, cell) in line.iter().enumerate() {
                 if let Plant::Wallbush(wallbush) = &cell.plant {
-                    if wallbush.growth == Wallbush::GROWTH_TO_BE_READY {
+                    if wallbush.growth >= ruleset.wallbush.growth_to_be_ready {
                         wallbushes.insert(Pos {
                             x: x as i32,
                             y: y as i32,
@@ -134,8 +208,8 @@ impl Map {
         wallbushes
     }
 
-    pub fn get_stones(&self) -> HashSet<Pos> {
-        let mut stones = HashSet::new();
+    pub fn get_stones(&self) -> BTreeSet<Pos> {
+        let mut stones = BTreeSet::new();
         for (y, line) in self.map.iter().enumerate() {
             for (x, cell) in line.iter().enumerate() {
                 if let Ground::Stone = cell.ground {
@@ -169,15 +243,20 @@ impl Map {
             Direction::Down,
             Direction::Left,
         ] {
-            let pos = pos.get_next_pos_on_map(Some(direction), self.map.len() as i32);
-            neighbours.push(self.get_cell(&pos).to_owned());
+            if let Some(pos) = self.get_next_pos(pos, Some(direction)) {
+                neighbours.push(self.get_cell(&pos).to_owned());
+            }
         }
         neighbours
     }
 
-    pub fn update_map(&mut self, active_swapshrooms: &mut HashMap<u32, (Pos, Pos)>) {
+    pub fn update_map(
+        &mut self,
+        active_swapshrooms: &mut BTreeMap<u32, (Pos, Pos)>,
+        ruleset: &Ruleset,
+    ) {
         let map_clone = self.clone();
-        let mut grown_inactive_swapshrooms = HashMap::<u32, Vec<Pos>>::new();
+        let mut grown_inactive_swapshrooms = BTreeMap::<u32, Vec<Pos>>::new();
 
         for (y, line) in self.map.iter_mut().enumerate() {
             for (x, cell) in line.iter_mut().enumerate() {
@@ -187,12 +266,12 @@ impl Map {
                 };
 
                 let neighbours = map_clone.get_neighbours(&pos);
-                let mut growt_rate = 1u8;
+                let mut growt_rate = ruleset.growth_rate;
                 let mut cactus_in_the_neighbours = false;
                 for n_cell in neighbours.iter() {
-                    // Water boosts the growth rate by 2
+                    // Water boosts the growth rate
                     if let Ground::Water = n_cell.ground {
-                        growt_rate = 2;
+                        growt_rate = ruleset.water_growth_rate;
                     }
                     // Cactus kills
                     if let Plant::Cactus(_) = n_cell.plant {
@@ -208,6 +287,7 @@ impl Map {
                     continue;
                 }
 
+                // A growth rate not dividing a threshold steps over it, so the harvests check `>=`
                 match &mut cell.plant {
                     Plant::None => {
                         if let Ground::Dirt = cell.ground {
@@ -215,19 +295,20 @@ impl Map {
                         }
                     }
                     Plant::Wheat(wheat) => {
-                        if wheat.growth < Wheat::GROWTH_TO_GRAINS {
+                        if wheat.growth < ruleset.wheat.growth_to_grains {
                             wheat.growth += growt_rate;
                         }
                     }
                     Plant::Bush(bush) => {
-                        if bush.growth < Bush::GROWTH_TO_WOOD {
+                        let rules = &ruleset.bush;
+                        if bush.growth < rules.growth_to_wood {
                             bush.growth += growt_rate;
                         } else if bush.growth
-                            < Bush::GROWTH_TO_WOOD + Bush::GROWTH_PER_BERRIES * Bush::MAX_BERRIES
+                            < rules.growth_to_wood + rules.growth_per_berries * rules.max_berries
                         {
                             bush.growth += growt_rate;
                             bush.berries +=
-                                (bush.growth - Bush::GROWTH_TO_WOOD) / Bush::GROWTH_PER_BERRIES;
+                                (bush.growth - rules.growth_to_wood) / rules.growth_per_berries;
                         }
                     }
                     Plant::Tree(tree) => {
@@ -237,55 +318,53 @@ impl Map {
                                 continue;
                             }
                         }
-                        if tree.growth < Tree::GROWTH_TO_WOOD {
+                        if tree.growth < ruleset.tree.growth_to_wood {
                             tree.growth += growt_rate;
                         }
                     }
                     Plant::Cane(cane) => {
-                        if cane.growth < Cane::GROWTH_TO_SUGAR {
+                        if cane.growth < ruleset.cane.growth_to_sugar {
                             cane.growth += growt_rate;
                         }
                     }
                     Plant::Pumpkin(pumpkin) => {
+                        let growth_to_pumpkinseed = ruleset.pumpkin.growth_to_pumpkinseed;
                         let mut next_max_size = 1;
                         for n_cell in neighbours {
                             if let Plant::Pumpkin(pumpkin_) = n_cell.plant {
-                                if pumpkin_.growth >= Pumpkin::GROWTH_TO_PUMPKINSEED {
+                                if pumpkin_.growth >= growth_to_pumpkinseed {
                                     next_max_size += 1;
                                 }
                             }
                         }
                         pumpkin.max_size = next_max_size;
 
-                        match (Pumpkin::GROWTH_TO_PUMPKINSEED * pumpkin.max_size)
-                            .cmp(&pumpkin.growth)
-                        {
+                        match (growth_to_pumpkinseed * pumpkin.max_size).cmp(&pumpkin.growth) {
                             Ordering::Less => {
                                 pumpkin.growth -= growt_rate;
-                                pumpkin.current_size =
-                                    pumpkin.growth / Pumpkin::GROWTH_TO_PUMPKINSEED;
+                                pumpkin.current_size = pumpkin.growth / growth_to_pumpkinseed;
                             }
                             Ordering::Equal => (),
                             Ordering::Greater => {
                                 pumpkin.growth += growt_rate;
-                                pumpkin.current_size =
-                                    pumpkin.growth / Pumpkin::GROWTH_TO_PUMPKINSEED;
+                                pumpkin.current_size = pumpkin.growth / growth_to_pumpkinseed;
                             }
                         }
                     }
                     Plant::Cactus(cactus) => {
-                        if cactus.growth < Cactus::GROWTH_PER_CACTUSMEAT * Cactus::MAX_CACTUSMEAT {
+                        let rules = &ruleset.cactus;
+                        if cactus.growth < rules.growth_per_cactusmeat * rules.max_cactusmeat {
                             cactus.growth += growt_rate;
-                            cactus.size = cactus.growth / Cactus::GROWTH_PER_CACTUSMEAT;
+                            cactus.size = cactus.growth / rules.growth_per_cactusmeat;
                         }
                     }
                     Plant::Wallbush(wallbush) => {
-                        if wallbush.growth < Wallbush::GROWTH_TO_BE_READY {
+                        if wallbush.growth < ruleset.wallbush.growth_to_be_ready {
                             wallbush.growth += growt_rate;
                         }
                     }
                     Plant::Swapshroom(swapshroom) => {
-                        if swapshroom.growth < Swapshroom::GROWTH_TO_BE_READY {
+                        if swapshroom.growth < ruleset.swapshroom.growth_to_be_ready {
                             swapshroom.growth += growt_rate;
                         } else if !swapshroom.active {
                             match grown_inactive_swapshrooms.entry(swapshroom.pair_id) {
@@ -299,7 +378,7 @@ impl Map {
                         }
                     }
                     Plant::Sunflower(sunflower) => {
-                        if sunflower.growth < Sunflower::GROWTH_TO_POWER {
+                        if sunflower.growth < ruleset.sunflower.growth_to_power {
                             sunflower.growth += growt_rate;
                         }
                     }
@@ -333,12 +412,12 @@ impl Map {
         &self,
         drawer: &mut Drawer,
         players: &HashMap<Pos, String>,
+        ruleset: &Ruleset,
     ) {
-        let map_size = self.map.len();
-        let mut map = vec![vec![" ".to_string(); map_size * 4]; map_size * 2];
+        let mut map = vec![vec![" ".to_string(); self.width() * 4]; self.height() * 2];
         for (y, line) in self.map.iter().enumerate() {
             for (x, cell) in line.iter().enumerate() {
-                let c = cell.to_ansi();
+                let c = cell.to_ansi(ruleset);
                 if let Some(player_name) = players.get(&Pos {
                     x: x as i32,
                     y: y as i32,
@@ -0,0 +1,142 @@
+use std::path::Path;
+
+use crate::{
+    cell::Cell,
+    config::named_file,
+    ground::Ground,
+    map::{Map, Topology},
+    plant::{Bush, Cactus, Cane, Plant, Pumpkin, Tree, Wallbush, Wheat},
+    ruleset::Ruleset,
+};
+
+/// Lines starting with it are ignored
+const COMMENT: char = '#';
+
+const LEGEND: &str = "\
+# One Cell per word, one row per line, all rows are the same length
+# Ground: d Dirt, t Tiled, s Sand, w Water, x Stone (a Player starts on each Stone)
+# Plant, fully grown: . None, W Wheat, B Bush, T Tree, C Cane, P Pumpkin, I Cactus, H Wallbush
+";
+
+impl Map {
+    /// `<maps_dir>/<name>.map`, it needs a Stone for each Player
+    pub fn load(
+        maps_dir: &Path,
+        name: &str,
+        number_of_players: u32,
+        ruleset: &Ruleset,
+    ) -> Result<Map, String> {
+        let file_name = named_file(maps_dir, name, "map")?;
+        let content = std::fs::read_to_string(&file_name)
+            .map_err(|err| format!("Unable to read map file `{:?}`: `{}`", file_name, err))?;
+        let map = Map::parse(&content, ruleset)
+            .map_err(|err| format!("Invalid map file `{:?}`: {}", file_name, err))?;
+        let stones = map.get_stones().len();
+        if stones < number_of_players as usize {
+            return Err(format!(
+                "Map `{}` has `{}` Stones to start on, for `{}` Players",
+                name, stones, number_of_players
+            ));
+        }
+        Ok(map)
+    }
+
+    pub fn parse(content: &str, ruleset: &Ruleset) -> Result<Map, String> {
+        let mut map = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(COMMENT) {
+                continue;
+            }
+            let row = line
+                .split_whitespace()
+                .map(|word| parse_cell(word, ruleset))
+                .collect::<Option<Vec<Cell>>>()
+                .ok_or_else(|| format!("Unknown Cell on line {}", i + 1))?;
+            map.push(row);
+        }
+        if map.is_empty() || map.iter().any(|row| row.len() != map[0].len()) {
+            return Err("The rows of the map have to be the same length".to_string());
+        }
+        Ok(Map::from_cells(map, Topology::default()))
+    }
+
+    /// The Plants are written as fully grown, Swapshrooms and Sunflowers are left out
+    pub fn to_map_file(&self) -> String {
+        let mut content = LEGEND.to_string();
+        for row in self.cells() {
+            let words = row.iter().map(format_cell).collect::<Vec<String>>();
+            content.push_str(&words.join(" "));
+            content.push('\n');
+        }
+        content
+    }
+}
+
+fn parse_cell(word: &str, ruleset: &Ruleset) -> Option<Cell> {
+    let mut chars = word.chars();
+    let ground = match chars.next()? {
+        'd' => Ground::Dirt,
+        't' => Ground::Tiled,
+        's' => Ground::Sand,
+        'w' => Ground::Water,
+        'x' => Ground::Stone,
+        _ => return None,
+    };
+    let plant = match chars.next()? {
+        '.' => Plant::None,
+        'W' => Plant::Wheat(Wheat {
+            growth: ruleset.wheat.growth_to_grains,
+        }),
+        'B' => Plant::Bush(Bush {
+            growth: ruleset.bush.growth_to_wood
+                + ruleset.bush.growth_per_berries * ruleset.bush.max_berries,
+            berries: ruleset.bush.max_berries,
+        }),
+        'T' => Plant::Tree(Tree {
+            growth: ruleset.tree.growth_to_wood,
+        }),
+        'C' => Plant::Cane(Cane {
+            growth: ruleset.cane.growth_to_sugar,
+        }),
+        'P' => Plant::Pumpkin(Pumpkin {
+            growth: ruleset.pumpkin.growth_to_pumpkinseed,
+            current_size: 1,
+            max_size: 1,
+        }),
+        'I' => Plant::Cactus(Cactus {
+            growth: ruleset.cactus.growth_per_cactusmeat * ruleset.cactus.max_cactusmeat,
+            size: ruleset.cactus.max_cactusmeat,
+        }),
+        'H' => Plant::Wallbush(Wallbush {
+            growth: ruleset.wallbush.growth_to_be_ready,
+            health: ruleset.wallbush.max_health,
+        }),
+        _ => return None,
+    };
+    match chars.next() {
+        Some(_) => None,
+        None => Some(Cell { ground, plant }),
+    }
+}
+
+fn format_cell(cell: &Cell) -> String {
+    let ground = match cell.ground {
+        Ground::Dirt => 'd',
+        Ground::Tiled => 't',
+        Ground::Sand => 's',
+        Ground::Water => 'w',
+        Ground::Stone => 'x',
+    };
+    let plant = match cell.plant {
+        Plant::Wheat(_) => 'W',
+        Plant::Bush(_) => 'B',
+        Plant::Tree(_) => 'T',
+        Plant::Cane(_) => 'C',
+        Plant::Pumpkin(_) => 'P',
+        Plant::Cactus(_) => 'I',
+        Plant::Wallbush(_) => 'H',
+        Plant::None | Plant::Swapshroom(_) | Plant::Sunflower(_) => '.',
+    };
+    format!("{}{}", ground, plant)
+}
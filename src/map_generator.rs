@@ -0,0 +1,221 @@
+use rand::{seq::SliceRandom, Rng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{cell::Cell, ground::Ground, map::Topology, plant::Plant, pos::Pos};
+
+pub use farmio_protocol::map_generator::MapGenerator;
+
+/// Arranges the `cells` into `height` lines of `width` Cells,
+/// a symmetric map is square and gets its Stones from `symmetrize` instead
+pub fn arrange(
+    map_generator: &MapGenerator,
+    mut cells: Vec<Cell>,
+    (width, height): (usize, usize),
+    symmetric_players: Option<u32>,
+    topology: &Topology,
+    rng: &mut Xoshiro256PlusPlus,
+) -> Vec<Vec<Cell>> {
+    let symmetry = symmetric_players.map(|number_of_players| Symmetry {
+        map_size: width,
+        rotations: number_of_players as usize,
+        // A bounded map turns around its center, a torus around any Cell
+        pivot: match topology {
+            Topology::Torus => 0,
+            Topology::Bounded => width - 1,
+        },
+    });
+    match map_generator {
+        MapGenerator::Shuffled => cells.shuffle(rng),
+        MapGenerator::Clustered => cells = cluster(cells, (width, height), &symmetry, rng),
+    }
+    let mut cells = cells.into_iter();
+    let map = (0..height)
+        .map(|_| cells.by_ref().take(width).collect())
+        .collect();
+    match symmetry {
+        Some(symmetry) => symmetry.symmetrize(map),
+        None => map,
+    }
+}
+
+/// Rotations of a square map, by a half turn for 2 Players and a quarter turn for 4
+struct Symmetry {
+    map_size: usize,
+    rotations: usize,
+    /// Twice the coordinates of the center of the rotations
+    pivot: usize,
+}
+
+/// Each biome takes the extreme cells of its own noise field: Water the lowest of `wet`,
+/// Sand the highest of `dry` with the Canes nearest to Water, Tiled the highest of `fertile`
+fn cluster(
+    cells: Vec<Cell>,
+    (width, height): (usize, usize),
+    symmetry: &Option<Symmetry>,
+    rng: &mut Xoshiro256PlusPlus,
+) -> Vec<Cell> {
+    let wet = smooth_noise((width, height), symmetry, rng);
+    let dry = smooth_noise((width, height), symmetry, rng);
+    let fertile = smooth_noise((width, height), symmetry, rng);
+
+    let mut water = Vec::new();
+    let mut sand = Vec::new();
+    let mut tiled = Vec::new();
+    let mut stone = Vec::new();
+    let mut dirt = Vec::new();
+    for cell in cells {
+        match cell.ground {
+            Ground::Water => water.push(cell),
+            Ground::Sand => sand.push(cell),
+            Ground::Tiled => tiled.push(cell),
+            Ground::Stone => stone.push(cell),
+            Ground::Dirt => dirt.push(cell),
+        }
+    }
+    // Planted Sand first, it ends up on the shores
+    sand.sort_by_key(|cell| matches!(cell.plant, Plant::None));
+
+    let mut free = (0..width * height).collect::<Vec<usize>>();
+    let mut placed: Vec<Option<Cell>> = vec![None; width * height];
+    for (biome, field, highest) in [(water, &wet, false), (tiled, &fertile, true)] {
+        place(biome, &mut free, &mut placed, field, highest);
+    }
+    // Sand is picked by `dry` but filled by `wet`, so the Canes are next to the lakes
+    sort_by_field(&mut free, &dry, true);
+    let mut sand_spots = free
+        .drain(..sand.len().min(free.len()))
+        .collect::<Vec<usize>>();
+    sort_by_field(&mut sand_spots, &wet, false);
+    for (i, cell) in sand_spots.into_iter().zip(sand) {
+        placed[i] = Some(cell);
+    }
+    // The Players start anywhere on the fields
+    free.shuffle(rng);
+    for (i, cell) in free.into_iter().zip(stone.into_iter().chain(dirt)) {
+        placed[i] = Some(cell);
+    }
+
+    placed.into_iter().flatten().collect()
+}
+
+/// Puts the `biome` onto the free spots with the lowest (or highest) `field` values
+fn place(
+    biome: Vec<Cell>,
+    free: &mut Vec<usize>,
+    placed: &mut [Option<Cell>],
+    field: &[f64],
+    highest: bool,
+) {
+    sort_by_field(free, field, highest);
+    for (i, cell) in free.drain(..biome.len().min(free.len())).zip(biome) {
+        placed[i] = Some(cell);
+    }
+}
+
+fn sort_by_field(spots: &mut [usize], field: &[f64], highest: bool) {
+    spots.sort_by(|a, b| {
+        let ordering = field[*a].total_cmp(&field[*b]).then(a.cmp(b));
+        if highest {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+/// White noise blurred on the torus, larger maps get larger blobs,
+/// with a `symmetry` it is the same in each rotation of the map
+fn smooth_noise(
+    (width, height): (usize, usize),
+    symmetry: &Option<Symmetry>,
+    rng: &mut Xoshiro256PlusPlus,
+) -> Vec<f64> {
+    let mut field = (0..width * height)
+        .map(|_| rng.random::<f64>())
+        .collect::<Vec<f64>>();
+    let passes = (width.min(height) / 4).clamp(1, 6);
+    for _ in 0..passes {
+        let mut blurred = vec![0.0; field.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for dy in [height - 1, 0, 1] {
+                    for dx in [width - 1, 0, 1] {
+                        sum += field[((y + dy) % height) * width + (x + dx) % width];
+                    }
+                }
+                blurred[y * width + x] = sum / 9.0;
+            }
+        }
+        field = blurred;
+    }
+    if let Some(symmetry) = symmetry {
+        let mut symmetric = vec![0.0; field.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let orbit = symmetry.orbit(x, y);
+                let sum = orbit.iter().map(|(x, y)| field[y * width + x]).sum::<f64>();
+                symmetric[y * width + x] = sum / orbit.len() as f64;
+            }
+        }
+        field = symmetric;
+    }
+    field
+}
+
+impl Symmetry {
+    /// Copies the first half (or quarter) of the map into the other rotations,
+    /// then puts the Stones at the same spot of each rotation, a half map apart
+    fn symmetrize(&self, map: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
+        let mut symmetric = map.clone();
+        for (y, line) in symmetric.iter_mut().enumerate() {
+            for (x, cell) in line.iter_mut().enumerate() {
+                let (source_x, source_y) = self
+                    .orbit(x, y)
+                    .into_iter()
+                    .min_by_key(|&(x, y)| (!self.in_source(x, y), y, x))
+                    .unwrap_or((x, y));
+                *cell = map[source_y][source_x].clone();
+            }
+        }
+        for spawn in self.spawns() {
+            symmetric[spawn.y as usize][spawn.x as usize] = Cell {
+                ground: Ground::Stone,
+                plant: Plant::None,
+            };
+        }
+        symmetric
+    }
+
+    /// One per rotation, `Pos` of the Stones where the Players start
+    fn spawns(&self) -> Vec<Pos> {
+        let quarter = self.map_size / 4;
+        self.orbit(quarter, quarter)
+            .into_iter()
+            .map(|(x, y)| Pos {
+                x: x as i32,
+                y: y as i32,
+            })
+            .collect()
+    }
+
+    fn orbit(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let n = self.map_size;
+        let mut orbit = vec![(x, y)];
+        for _ in 1..self.rotations {
+            let (x, y) = orbit[orbit.len() - 1];
+            orbit.push(match self.rotations {
+                4 => ((self.pivot + n - y) % n, x),
+                _ => ((self.pivot + n - x) % n, (self.pivot + n - y) % n),
+            });
+        }
+        orbit
+    }
+
+    fn in_source(&self, x: usize, y: usize) -> bool {
+        match self.rotations {
+            4 => x < self.map_size / 2 && y < self.map_size / 2,
+            _ => y < self.map_size / 2,
+        }
+    }
+}
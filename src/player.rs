@@ -1,6 +1,7 @@
 use std::collections::{hash_map::Entry, HashMap};
 
 use tokio::sync::mpsc::Sender;
+use uuid::Uuid;
 
 use crate::{game::MsgToPlayer, harvest::Harvest, pos::Pos, seed::Seed};
 
@@ -13,30 +14,41 @@ pub struct Player {
     pub points: u32,
     pub next_swapshroom_pair_id: Option<u32>,
     pub connected: bool,
+    /// Secret of the Player, required to reconnect
+    pub token: String,
+    /// The connection which is allowed to act in the name of the Player
+    pub connection_id: Uuid,
+    /// `id` of the request of the Action to play this turn
+    pub request_id: Option<u64>,
 }
 
 impl Player {
-    pub fn new(player_name: String, to_player_tx: Sender<String>, pos: Pos) -> Self {
+    pub fn new(
+        player_name: String,
+        to_player_tx: Sender<String>,
+        pos: Pos,
+        harvests: HashMap<Harvest, u32>,
+        seeds: HashMap<Seed, u32>,
+    ) -> Self {
         Self {
             player_name,
             to_player_tx,
             pos,
-            harvests: HashMap::from([
-                (Harvest::Grains, 999),
-                (Harvest::Berry, 999),
-                (Harvest::Wood, 999),
-                (Harvest::Sugar, 999),
-                (Harvest::PumpkinSeed, 999),
-                (Harvest::CactusMeat, 999),
-                (Harvest::Power, 999),
-            ]),
-            seeds: HashMap::new(),
+            harvests,
+            seeds,
             points: 0,
             next_swapshroom_pair_id: None,
             connected: true,
+            token: Uuid::new_v4().simple().to_string(),
+            connection_id: Uuid::nil(),
+            request_id: None,
         }
     }
 
+    pub fn has_token(&self, token: &Option<String>) -> bool {
+        token.as_deref() == Some(self.token.as_str())
+    }
+
     pub fn harvest(&mut self, harvest: Harvest, volume: u32, points: u32) -> MsgToPlayer {
         match self.harvests.entry(harvest.clone()) {
             Entry::Occupied(occupied_entry) => {
@@ -0,0 +1,396 @@
+use std::{
+    fmt::Write,
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+    path::PathBuf,
+};
+
+use clap::ValueEnum;
+use gif::{Encoder, Frame, Repeat};
+
+use crate::{game::Game, map::Map, plant::Plant, pos::Pos, ruleset::Ruleset};
+
+/// Same as the viewer, by join order
+const PLAYER_COLORS: [[u8; 3]; 6] = [
+    [0xff, 0x50, 0x50],
+    [0x50, 0xa0, 0xff],
+    [0xff, 0xff, 0xff],
+    [0xff, 0x50, 0xff],
+    [0x50, 0xff, 0xd0],
+    [0xff, 0xa0, 0x50],
+];
+
+/// What the frames of a replayed Game are rendered to
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum RenderFormat {
+    /// `<output_dir>/<game_name>.frames/<turn>.svg`
+    Svg,
+    /// `<output_dir>/<game_name>.frames/<turn>.png`
+    Png,
+    /// `<output_dir>/<game_name>.gif`, one frame per turn
+    Gif,
+}
+
+/// Renders a Game after every turn, into images of `cell_size` pixels per Cell
+pub struct Renderer {
+    format: RenderFormat,
+    path: PathBuf,
+    cell_size: u32,
+    /// Hundredths of a second, as in GIF
+    frame_delay: u16,
+    /// Created with the first frame, the frames are written as they are rendered
+    gif: Option<Encoder<BufWriter<File>>>,
+}
+
+impl Renderer {
+    pub async fn new(
+        output_dir: &Path,
+        game_name: &str,
+        format: RenderFormat,
+        cell_size: u32,
+        frame_delay_ms: u64,
+    ) -> Self {
+        let path = match format {
+            RenderFormat::Svg | RenderFormat::Png => {
+                let dir = output_dir.join(format!("{}.frames", game_name));
+                // The frames of a previous Game with the same name are outdated
+                if tokio::fs::metadata(&dir).await.is_ok() {
+                    if let Err(err) = tokio::fs::remove_dir_all(&dir).await {
+                        eprintln!("Unable to clear directory {:?}: `{}`", dir, err);
+                    }
+                }
+                tokio::fs::create_dir_all(&dir)
+                    .await
+                    .unwrap_or_else(|_| panic!("Unable to create directory: {:?}", dir));
+                dir
+            }
+            RenderFormat::Gif => output_dir.join(format!("{}.gif", game_name)),
+        };
+        Self {
+            format,
+            path,
+            cell_size: cell_size.max(4),
+            frame_delay: (frame_delay_ms / 10).clamp(1, u16::MAX as u64) as u16,
+            gif: None,
+        }
+    }
+
+    pub async fn render(&mut self, game: &Game) {
+        let players = game
+            .player_order()
+            .iter()
+            .map(|player_uuid| {
+                let player = &game.players()[player_uuid];
+                (player.pos.clone(), player.player_name.clone())
+            })
+            .collect::<Vec<(Pos, String)>>();
+        let map = game.map();
+        let file_name = self.path.join(frame_file_name(game.turns(), self.format));
+        let content = match self.format {
+            RenderFormat::Svg => {
+                map_to_svg(map, &players, game.ruleset(), self.cell_size).map(String::into_bytes)
+            }
+            RenderFormat::Png => map_to_rgb(map, &players, game.ruleset(), self.cell_size)
+                .and_then(|(width, height, pixels)| {
+                    encode_png(width, height, &pixels).map_err(|err| err.to_string())
+                }),
+            RenderFormat::Gif => {
+                if let Err(err) = self.add_gif_frame(map, &players, game.ruleset()) {
+                    eprintln!("Unable to encode turn #{}: `{}`", game.turns(), err);
+                }
+                return;
+            }
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(err) => {
+                eprintln!("Unable to encode turn #{}: `{}`", game.turns(), err);
+                return;
+            }
+        };
+        if let Err(err) = tokio::fs::write(&file_name, content).await {
+            eprintln!("Unable to write frame file `{:?}`: `{}`", file_name, err);
+        }
+    }
+
+    /// Ends the GIF, the other formats are complete frame by frame
+    pub async fn finish(self) {
+        let Some(encoder) = self.gif else {
+            return;
+        };
+        // The trailer of the GIF is written when the encoder is done
+        let result = match encoder.into_inner() {
+            Ok(mut file) => io::Write::flush(&mut file).map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        if let Err(err) = result {
+            eprintln!("Unable to write GIF file `{:?}`: `{}`", self.path, err);
+        }
+    }
+
+    fn add_gif_frame(
+        &mut self,
+        map: &Map,
+        players: &[(Pos, String)],
+        ruleset: &Ruleset,
+    ) -> Result<(), String> {
+        // Checked before rendering, a GIF is at most 65535 pixels wide
+        let (width, height) = image_size(map, self.cell_size)?;
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(format!("{}x{} is too large for a GIF", width, height)),
+        };
+        if self.gif.is_none() {
+            let file = File::create(&self.path)
+                .map_err(|err| format!("Unable to create `{:?}`: `{}`", self.path, err))?;
+            let mut encoder = Encoder::new(BufWriter::new(file), width, height, &[])
+                .map_err(|err| err.to_string())?;
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(|err| err.to_string())?;
+            self.gif = Some(encoder);
+        }
+        let (_, _, pixels) = map_to_rgb(map, players, ruleset, self.cell_size)?;
+        let mut frame = to_gif_frame(width, height, &pixels);
+        frame.delay = self.frame_delay;
+        if let Some(encoder) = &mut self.gif {
+            encoder.write_frame(&frame).map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Zero padded, so the files are sorted by turn
+fn frame_file_name(turn: u32, format: RenderFormat) -> String {
+    match format {
+        RenderFormat::Png => format!("{:06}.png", turn),
+        _ => format!("{:06}.svg", turn),
+    }
+}
+
+/// `(width, height)` in pixels of the images of the map
+fn image_size(map: &Map, cell_size: u32) -> Result<(u32, u32), String> {
+    let side = |cells: usize| {
+        u32::try_from(cells)
+            .ok()
+            .and_then(|cells| cells.checked_mul(cell_size))
+    };
+    match (side(map.width()), side(map.height())) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(format!(
+            "The {}x{} map is too large for {} pixels per Cell",
+            map.width(),
+            map.height(),
+            cell_size
+        )),
+    }
+}
+
+/// Each Cell shows its 2 lines of `Cell::to_ansi` in its colors,
+/// the Players are rings with their names, in join order
+pub fn map_to_svg(
+    map: &Map,
+    players: &[(Pos, String)],
+    ruleset: &Ruleset,
+    cell_size: u32,
+) -> Result<String, String> {
+    let (width, height) = image_size(map, cell_size)?;
+    let size = cell_size as f64;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="{:.1}" text-anchor="middle">"#,
+        width,
+        height,
+        size / 4.0,
+    );
+    for (y, line) in map.cells().iter().enumerate() {
+        for (x, cell) in line.iter().enumerate() {
+            let (background, foreground, subcells) = cell.ansi_parts(ruleset);
+            let (left, top) = (x as f64 * size, y as f64 * size);
+            let _ = writeln!(
+                svg,
+                r##"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="#00000040"/>"##,
+                left,
+                top,
+                size,
+                size,
+                to_hex(ansi_to_rgb(background)),
+            );
+            if matches!(cell.plant, Plant::None) {
+                continue;
+            }
+            for (row, chars) in subcells.chunks(4).enumerate() {
+                let text = chars.iter().collect::<String>();
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" fill="{}" xml:space="preserve">{}</text>"#,
+                    left + size / 2.0,
+                    top + size * (0.4 + 0.35 * row as f64),
+                    to_hex(ansi_to_rgb(foreground)),
+                    escape(&text),
+                );
+            }
+        }
+    }
+    for (i, (pos, player_name)) in players.iter().enumerate() {
+        let (cx, cy) = ((pos.x as f64 + 0.5) * size, (pos.y as f64 + 0.5) * size);
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="none" stroke="{}" stroke-width="{:.1}"/>"#,
+            cx,
+            cy,
+            size / 3.0,
+            to_hex(PLAYER_COLORS[i % PLAYER_COLORS.len()]),
+            (size / 12.0).max(1.0),
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" fill="#ffffff" stroke="#000000" stroke-width="0.5">{}</text>"##,
+            cx,
+            cy - size / 3.0 - 2.0,
+            escape(player_name),
+        );
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// `(width, height, pixels)` of the map, without text: the Plants are squares
+/// of the foreground color of `Cell::to_ansi` and the Players are rings
+pub fn map_to_rgb(
+    map: &Map,
+    players: &[(Pos, String)],
+    ruleset: &Ruleset,
+    cell_size: u32,
+) -> Result<(u32, u32, Vec<u8>), String> {
+    let (image_width, image_height) = image_size(map, cell_size)?;
+    let size = cell_size as usize;
+    let width = image_width as usize;
+    let len = width
+        .checked_mul(image_height as usize)
+        .and_then(|len| len.checked_mul(3))
+        .ok_or_else(|| format!("A {}x{} image is too large", image_width, image_height))?;
+    let mut pixels = vec![0; len];
+    let mut put = |x: usize, y: usize, color: [u8; 3]| {
+        let i = (y * width + x) * 3;
+        pixels[i..i + 3].copy_from_slice(&color);
+    };
+    for (y, line) in map.cells().iter().enumerate() {
+        for (x, cell) in line.iter().enumerate() {
+            let (background, foreground, _) = cell.ansi_parts(ruleset);
+            let background = ansi_to_rgb(background);
+            // Darker border, so the Cells of the same Ground stay apart
+            let border = background.map(|c| (c as u32 * 3 / 4) as u8);
+            let plant = (!matches!(cell.plant, Plant::None)).then(|| ansi_to_rgb(foreground));
+            for dy in 0..size {
+                for dx in 0..size {
+                    let color = if dx == 0 || dy == 0 {
+                        border
+                    } else {
+                        match plant {
+                            Some(plant)
+                                if (size / 4..size - size / 4).contains(&dx)
+                                    && (size / 4..size - size / 4).contains(&dy) =>
+                            {
+                                plant
+                            }
+                            _ => background,
+                        }
+                    };
+                    put(x * size + dx, y * size + dy, color);
+                }
+            }
+        }
+    }
+    let radius = size as f64 / 2.5;
+    let thickness = (size as f64 / 8.0).max(1.5);
+    for (i, (pos, _)) in players.iter().enumerate() {
+        let color = PLAYER_COLORS[i % PLAYER_COLORS.len()];
+        for dy in 0..size {
+            for dx in 0..size {
+                let distance = (dx as f64 + 0.5 - size as f64 / 2.0)
+                    .hypot(dy as f64 + 0.5 - size as f64 / 2.0);
+                if distance <= radius && distance > radius - thickness {
+                    put(
+                        pos.x as usize * size + dx,
+                        pos.y as usize * size + dy,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+    Ok((image_width, image_height, pixels))
+}
+
+fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Result<Vec<u8>, png::EncodingError> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(png)
+}
+
+/// The frames only use a few colors, so they fit an exact palette,
+/// quantized otherwise
+fn to_gif_frame(width: u16, height: u16, pixels: &[u8]) -> Frame<'static> {
+    let mut palette = Vec::<[u8; 3]>::new();
+    let mut indexes = Vec::with_capacity(pixels.len() / 3);
+    for color in pixels.chunks(3) {
+        let index = match palette.iter().position(|c| c == color) {
+            Some(index) => index,
+            None if palette.len() < 256 => {
+                palette.push([color[0], color[1], color[2]]);
+                palette.len() - 1
+            }
+            None => return Frame::from_rgb_speed(width, height, pixels, 10),
+        };
+        indexes.push(index as u8);
+    }
+    Frame::from_palette_pixels(width, height, indexes, palette.concat(), None)
+}
+
+/// The RGB color of a 256 colors terminal code
+pub fn ansi_to_rgb(code: u8) -> [u8; 3] {
+    const SYSTEM: [[u8; 3]; 16] = [
+        [0x00, 0x00, 0x00],
+        [0x80, 0x00, 0x00],
+        [0x00, 0x80, 0x00],
+        [0x80, 0x80, 0x00],
+        [0x00, 0x00, 0x80],
+        [0x80, 0x00, 0x80],
+        [0x00, 0x80, 0x80],
+        [0xc0, 0xc0, 0xc0],
+        [0x80, 0x80, 0x80],
+        [0xff, 0x00, 0x00],
+        [0x00, 0xff, 0x00],
+        [0xff, 0xff, 0x00],
+        [0x00, 0x00, 0xff],
+        [0xff, 0x00, 0xff],
+        [0x00, 0xff, 0xff],
+        [0xff, 0xff, 0xff],
+    ];
+    const LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+    match code {
+        0..=15 => SYSTEM[code as usize],
+        16..=231 => {
+            let i = (code - 16) as usize;
+            [LEVELS[i / 36], LEVELS[i / 6 % 6], LEVELS[i % 6]]
+        }
+        _ => [8 + (code - 232) * 10; 3],
+    }
+}
+
+fn to_hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,239 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    sync::mpsc::{self, Receiver},
+};
+use uuid::Uuid;
+
+use crate::{
+    drawer::Drawer,
+    game::{Action, Game, GameSettings},
+    map::Map,
+    pos::Pos,
+    render::{RenderFormat, Renderer},
+    ruleset::Ruleset,
+    snapshot::{SnapshotOutput, Snapshotter},
+};
+
+/// First line of a `.replay` file, the rest of the lines are `ReplayTurn`s
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub game_name: String,
+    pub game_settings: GameSettings,
+    #[serde(default)]
+    pub ruleset: Ruleset,
+    /// The map of a map file, it may have changed since
+    #[serde(default)]
+    pub map: Option<Map>,
+    /// In join order
+    pub players: Vec<ReplayPlayer>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayPlayer {
+    pub player_uuid: Uuid,
+    pub player_name: String,
+    pub pos: Pos,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayTurn {
+    pub turn: u32,
+    pub actions: BTreeMap<Uuid, Action>,
+}
+
+/// How the turns of a replay are rendered to images
+pub struct ReplayRender {
+    pub format: RenderFormat,
+    /// Pixels per Cell
+    pub cell_size: u32,
+    /// Between the frames of a GIF
+    pub frame_delay_ms: u64,
+}
+
+pub struct ReplayRecorder {
+    file: File,
+}
+
+impl ReplayRecorder {
+    pub async fn new(output_dir: &Path, game_name: &str) -> Self {
+        let file_name = output_dir.join(format!("{}.replay", game_name));
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&file_name)
+            .await
+            .unwrap_or_else(|_| panic!("Unable to Open file: {:?}", file_name));
+        Self { file }
+    }
+
+    /// Continues the replay of a restored Game,
+    /// dropping the turns recorded after the save was taken
+    pub async fn resume(output_dir: &Path, game_name: &str, turns: u32) -> Self {
+        let file_name = output_dir.join(format!("{}.replay", game_name));
+        let content = tokio::fs::read_to_string(&file_name)
+            .await
+            .unwrap_or_default();
+        let mut lines = content.lines();
+        let mut kept = String::new();
+        if let Some(header) = lines.next() {
+            kept.push_str(header);
+            kept.push('\n');
+        }
+        for line in lines {
+            match serde_json::from_str::<ReplayTurn>(line) {
+                Ok(replay_turn) if replay_turn.turn < turns => {
+                    kept.push_str(line);
+                    kept.push('\n');
+                }
+                _ => break,
+            }
+        }
+
+        let mut recorder = Self::new(output_dir, game_name).await;
+        if let Err(err) = recorder.file.write_all(kept.as_bytes()).await {
+            eprintln!("Unable to write to replay file: `{}`", err);
+        }
+        recorder
+    }
+
+    pub async fn write<L: Serialize + std::fmt::Debug>(&mut self, line: &L) {
+        let mut msg = match serde_json::to_string(line) {
+            Ok(msg) => msg,
+            Err(err) => {
+                eprintln!("Unable to serialize replay line `{:?}`: `{}`", line, err);
+                return;
+            }
+        };
+        msg.push('\n');
+        if let Err(err) = self.file.write_all(msg.as_bytes()).await {
+            eprintln!("Unable to write to replay file: `{}`", err);
+        }
+        if let Err(err) = self.file.flush().await {
+            eprintln!("Unable to flush the replay file: `{}`", err);
+        }
+    }
+}
+
+/// Re-simulates a recorded Game turn by turn,
+/// printing the messages the Players received
+pub async fn replay_game(
+    replay_file: PathBuf,
+    draw: bool,
+    turn_delay_ms: u64,
+    snapshots: bool,
+    render: Option<ReplayRender>,
+    output_dir: &Path,
+) {
+    let file = File::open(&replay_file)
+        .await
+        .unwrap_or_else(|_| panic!("Unable to Open file: {:?}", replay_file));
+    let mut lines = BufReader::new(file).lines();
+
+    let header = match lines.next_line().await {
+        Ok(Some(line)) => serde_json::from_str::<ReplayHeader>(&line)
+            .unwrap_or_else(|err| panic!("Invalid replay header `{}`: `{}`", line, err)),
+        _ => panic!("Empty replay file: {:?}", replay_file),
+    };
+    println!(
+        "Replaying Game `{}` ({:?})",
+        header.game_name, header.game_settings
+    );
+
+    let drawer = match draw {
+        true => Some(Drawer::new(output_dir, &format!("{}.replay", header.game_name)).await),
+        false => None,
+    };
+    let snapshotter = match snapshots {
+        true => Some(
+            Snapshotter::new(
+                output_dir,
+                &format!("{}.replay", header.game_name),
+                &SnapshotOutput::File,
+            )
+            .await,
+        ),
+        false => None,
+    };
+    let mut renderer = match render {
+        Some(render) => Some(
+            Renderer::new(
+                output_dir,
+                &format!("{}.replay", header.game_name),
+                render.format,
+                render.cell_size,
+                render.frame_delay_ms,
+            )
+            .await,
+        ),
+        None => None,
+    };
+    let (_to_game_tx, to_game_rx) = mpsc::channel(1);
+    let mut game = Game::new(
+        header.game_name,
+        to_game_rx,
+        header.game_settings,
+        header.ruleset,
+        header.map,
+        drawer,
+        None,
+        None,
+        snapshotter,
+    )
+    .await;
+
+    let mut to_player_rxs = Vec::<(String, Receiver<String>)>::new();
+    for player in header.players {
+        let (to_player_tx, to_player_rx) = mpsc::channel::<String>(1024);
+        to_player_rxs.push((player.player_name.clone(), to_player_rx));
+        game.add_player(
+            player.player_uuid,
+            player.player_name,
+            to_player_tx,
+            player.pos,
+        );
+    }
+    game.write_snapshot().await;
+    if let Some(renderer) = &mut renderer {
+        renderer.render(&game).await;
+    }
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let replay_turn = match serde_json::from_str::<ReplayTurn>(&line) {
+            Ok(replay_turn) => replay_turn,
+            Err(err) => {
+                eprintln!("Invalid replay turn `{}`: `{}`", line, err);
+                break;
+            }
+        };
+        println!("#{}", replay_turn.turn);
+        game.play_turn(replay_turn.actions).await;
+        if let Some(renderer) = &mut renderer {
+            renderer.render(&game).await;
+        }
+        for (player_name, to_player_rx) in to_player_rxs.iter_mut() {
+            while let Ok(msg) = to_player_rx.try_recv() {
+                println!("  `{}`: {}", player_name, msg);
+            }
+        }
+        if turn_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(turn_delay_ms)).await;
+        }
+    }
+
+    if let Some(renderer) = renderer {
+        renderer.finish().await;
+    }
+    println!("Replay of Game `{}` is over", game.game_name());
+    for player in game.players().values() {
+        println!("  `{}`: {} points", player.player_name, player.points);
+    }
+}
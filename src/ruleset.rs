@@ -0,0 +1,16 @@
+use std::path::Path;
+
+pub use farmio_protocol::ruleset::*;
+
+use crate::config::named_file;
+
+/// `<rulesets_dir>/<name>.json`
+pub fn load(rulesets_dir: &Path, name: &str) -> Result<Ruleset, String> {
+    let file_name = named_file(rulesets_dir, name, "json")?;
+    let content = std::fs::read_to_string(&file_name)
+        .map_err(|err| format!("Unable to read ruleset file `{:?}`: `{}`", file_name, err))?;
+    let ruleset: Ruleset = serde_json::from_str(&content)
+        .map_err(|err| format!("Invalid ruleset file `{:?}`: `{}`", file_name, err))?;
+    ruleset.validate()?;
+    Ok(ruleset)
+}
@@ -0,0 +1,74 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    game::GameSettings, harvest::Harvest, map::Map, pos::Pos, ruleset::Ruleset, seed::Seed,
+};
+
+/// The full state of a running Game, taken between two turns
+#[derive(Serialize, Deserialize)]
+pub struct GameSave {
+    pub game_name: String,
+    pub game_settings: GameSettings,
+    /// Not part of the serialized `GameSettings`
+    pub password: Option<String>,
+    #[serde(default)]
+    pub ruleset: Ruleset,
+    pub turns: u32,
+    pub rng: Xoshiro256PlusPlus,
+    /// In join order
+    pub players: Vec<SavedPlayer>,
+    pub map: Map,
+    pub active_swapshrooms: BTreeMap<u32, (Pos, Pos)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub player_uuid: Uuid,
+    pub player_name: String,
+    pub pos: Pos,
+    pub harvests: HashMap<Harvest, u32>,
+    pub seeds: HashMap<Seed, u32>,
+    pub points: u32,
+    pub next_swapshroom_pair_id: Option<u32>,
+    pub token: String,
+    /// The configured BOT playing as the Player, relaunched when the Game is restored
+    #[serde(default)]
+    pub bot: Option<String>,
+}
+
+impl GameSave {
+    pub async fn load(file_name: &Path) -> Self {
+        let content = tokio::fs::read_to_string(file_name)
+            .await
+            .unwrap_or_else(|err| panic!("Unable to read save file `{:?}`: `{}`", file_name, err));
+        serde_json::from_str(&content)
+            .unwrap_or_else(|err| panic!("Invalid save file `{:?}`: `{}`", file_name, err))
+    }
+}
+
+pub struct GameSaver {
+    file_name: PathBuf,
+}
+
+impl GameSaver {
+    pub fn new(output_dir: &Path, game_name: &str) -> Self {
+        Self {
+            file_name: output_dir.join(format!("{}.save", game_name)),
+        }
+    }
+
+    pub async fn save(&self, game_save: &GameSave) -> std::io::Result<()> {
+        let content = serde_json::to_string(game_save)?;
+        // Write then rename, so a crash can not leave a half written save behind
+        let tmp_file_name = self.file_name.with_extension("save.tmp");
+        tokio::fs::write(&tmp_file_name, content).await?;
+        tokio::fs::rename(&tmp_file_name, &self.file_name).await
+    }
+}
@@ -1,18 +1,21 @@
 use futures::SinkExt;
 use serde::Serialize;
+use serde_json::{Map, Value};
 use tokio::{
-    net::TcpStream,
+    io::{AsyncRead, AsyncWrite},
     sync::mpsc::{Receiver, Sender},
 };
 use tokio_util::codec::{Framed, LinesCodec};
 
-pub async fn send_to_player(
+pub async fn send_to_player<T: AsyncRead + AsyncWrite>(
     mut to_player_rx: Receiver<String>,
-    mut tcp_tx: futures::stream::SplitSink<Framed<TcpStream, LinesCodec>, String>,
+    mut tcp_tx: futures::stream::SplitSink<Framed<T, LinesCodec>, String>,
 ) {
     while let Some(msg) = to_player_rx.recv().await {
         if let Err(err) = tcp_tx.send(msg).await {
             eprintln!("Unable to send Msg to Player! (send_to_player): `{}`", err);
+            // The connection is gone, closing the channel lets the Game notice it
+            break;
         }
     }
 }
@@ -38,3 +41,27 @@ pub async fn send_msg_to_player<M: Serialize + std::fmt::Debug>(
         }
     }
 }
+
+/// With the `id` of the request the reply is an object too: `{ "id": 7, "GameCreated": null }`
+pub async fn send_reply_to_player<M: Serialize + std::fmt::Debug>(
+    to_player_tx: &mut Sender<String>,
+    request_id: Option<u64>,
+    msg_to_player: M,
+) {
+    let Some(request_id) = request_id else {
+        return send_msg_to_player(to_player_tx, msg_to_player).await;
+    };
+    let mut object = match serde_json::to_value(&msg_to_player) {
+        Ok(Value::Object(object)) => object,
+        Ok(Value::String(variant)) => Map::from_iter([(variant, Value::Null)]),
+        _ => {
+            eprintln!(
+                "Unable to serialize Message `{:?}` to Player",
+                msg_to_player
+            );
+            return;
+        }
+    };
+    object.insert("id".to_string(), request_id.into());
+    send_msg_to_player(to_player_tx, Value::Object(object)).await;
+}
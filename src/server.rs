@@ -1,26 +1,250 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc, time::Duration};
 
 use dashmap::DashMap;
-use tokio::{net::TcpListener, sync::mpsc::Sender};
+use tokio::{
+    net::TcpListener,
+    sync::{
+        mpsc::{self, Sender},
+        watch,
+    },
+    task::JoinHandle,
+};
 use tokio_util::codec::{Framed, LinesCodec};
+use uuid::Uuid;
 
-use crate::handle_connection::{handle_connection, PlayerAction};
+use crate::{
+    config::ServerConfig,
+    drawer::Drawer,
+    game::{Game, GamePhase, GameResult, GameSettings, GameStatus},
+    handle_connection::{handle_connection, PlayerAction},
+    map::Map,
+    replay::ReplayRecorder,
+    ruleset::Ruleset,
+    save::{GameSave, GameSaver},
+    snapshot::Snapshotter,
+    stdio_bot::launch_stdio_bot,
+    viewer::accept_http_connections,
+    websocket::accept_websocket_connections,
+};
 
-pub type Games = Arc<DashMap<String, Sender<PlayerAction>>>;
+pub type Games = Arc<DashMap<String, GameEntry>>;
 
-pub async fn start_server(ip_port: &str) {
+/// Finished Games stay listed for a while, so `ListGames` and `GameInfo` show how they ended
+const FINISHED_GAMES_LISTED_FOR: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone)]
+pub struct GameEntry {
+    pub to_game_tx: Sender<PlayerAction>,
+    /// Kept up to date by the Game
+    pub status: watch::Receiver<GameStatus>,
+}
+
+impl GameEntry {
+    pub fn is_finished(&self) -> bool {
+        self.status.borrow().phase == GamePhase::Finished
+    }
+}
+
+pub async fn start_server(config: ServerConfig) {
+    let listener = bind(&config).await;
+    let config = Arc::new(config);
+    let games: Games = Arc::new(DashMap::new());
+
+    for save_file in config.restore.iter() {
+        restore_game(&games, &config, GameSave::load(save_file).await).await;
+    }
+
+    if let Some(http_listener) = bind_http(&config).await {
+        let Some(websocket_port) = config.websocket_port else {
+            panic!("The viewer needs the WebSocket listener, set `websocket_port`!");
+        };
+        tokio::spawn(accept_http_connections(http_listener, websocket_port));
+    }
+    if let Some(websocket_listener) = bind_websocket(&config).await {
+        tokio::spawn(accept_websocket_connections(
+            websocket_listener,
+            games.clone(),
+            config.clone(),
+        ));
+    }
+
+    accept_connections(listener, games, config).await
+}
+
+/// Also creates the output directory
+pub async fn bind(config: &ServerConfig) -> TcpListener {
+    tokio::fs::create_dir_all(&config.output_dir)
+        .await
+        .unwrap_or_else(|_| {
+            panic!(
+                "Unable to create output directory: {:?}",
+                config.output_dir
+            )
+        });
+
+    let ip_port = config.ip_port();
     let listener = TcpListener::bind(&ip_port)
         .await
         .unwrap_or_else(|_| panic!("Unable to bind to address: {}", ip_port));
     println!("Listening on {}", ip_port);
+    listener
+}
 
-    let games: Games = Arc::new(DashMap::new());
-    // TODO: Remove Game from games when its over
+/// `None` if the WebSocket listener is disabled
+pub async fn bind_websocket(config: &ServerConfig) -> Option<TcpListener> {
+    let ip_port = config.websocket_ip_port()?;
+    let listener = TcpListener::bind(&ip_port)
+        .await
+        .unwrap_or_else(|_| panic!("Unable to bind to address: {}", ip_port));
+    println!("Listening for WebSockets on {}", ip_port);
+    Some(listener)
+}
+
+/// `None` if the viewer is disabled
+pub async fn bind_http(config: &ServerConfig) -> Option<TcpListener> {
+    let ip_port = config.http_ip_port()?;
+    let listener = TcpListener::bind(&ip_port)
+        .await
+        .unwrap_or_else(|_| panic!("Unable to bind to address: {}", ip_port));
+    println!("Serving the viewer on http://{}", ip_port);
+    Some(listener)
+}
 
+pub async fn accept_connections(listener: TcpListener, games: Games, config: Arc<ServerConfig>) {
     while let Ok((socket, _addr)) = listener.accept().await {
         let framed = Framed::new(socket, LinesCodec::new());
         let games = games.clone();
-        tokio::spawn(async move { handle_connection(framed, games).await });
+        let config = config.clone();
+        tokio::spawn(async move { handle_connection(framed, games, config).await });
     }
     unreachable!()
 }
+
+/// Creates a Game with its drawing, map, replay and save files in the `output_dir`,
+/// and starts its BOTs
+pub async fn create_game(
+    games: &Games,
+    config: &Arc<ServerConfig>,
+    game_name: &str,
+    game_settings: GameSettings,
+    ruleset: Ruleset,
+    map: Option<Map>,
+) -> (Sender<PlayerAction>, JoinHandle<GameResult>) {
+    let bots = game_settings.bots.clone();
+    let (to_game_tx, to_game_rx) = mpsc::channel::<PlayerAction>(1024);
+    let drawer = Drawer::new(&config.output_dir, game_name).await;
+    let recorder = ReplayRecorder::new(&config.output_dir, game_name).await;
+    let saver = GameSaver::new(&config.output_dir, game_name);
+    let snapshotter = match &config.snapshots {
+        Some(output) => Some(Snapshotter::new(&config.output_dir, game_name, output).await),
+        None => None,
+    };
+    let mut game = Game::new(
+        game_name.to_string(),
+        to_game_rx,
+        game_settings,
+        ruleset,
+        map,
+        Some(drawer),
+        Some(recorder),
+        Some(saver),
+        snapshotter,
+    )
+    .await;
+    let bots = bots
+        .into_iter()
+        .map(|bot_name| {
+            let player_uuid = Uuid::new_v4();
+            game.add_bot(player_uuid, bot_name.clone());
+            (player_uuid, bot_name)
+        })
+        .collect::<Vec<(Uuid, String)>>();
+    export_map(&config.output_dir, game_name, game.map()).await;
+    let game_handle = spawn_game(games, game, to_game_tx.clone());
+    for (player_uuid, bot_name) in bots.iter() {
+        launch_stdio_bot(games, config, game_name, bot_name, *player_uuid, None);
+    }
+    (to_game_tx, game_handle)
+}
+
+pub fn spawn_game(
+    games: &Games,
+    mut game: Game,
+    to_game_tx: Sender<PlayerAction>,
+) -> JoinHandle<GameResult> {
+    let game_name = game.game_name().to_string();
+    let game_entry = GameEntry {
+        to_game_tx: to_game_tx.clone(),
+        status: game.status(),
+    };
+    games.insert(game_name.clone(), game_entry);
+    let games = games.clone();
+    tokio::spawn(async move {
+        let game_result = game.run().await;
+        // The name can be reused after the Game is over, then it is not this entry anymore
+        tokio::spawn(async move {
+            tokio::time::sleep(FINISHED_GAMES_LISTED_FOR).await;
+            games.remove_if(&game_name, |_, game_entry| {
+                game_entry.to_game_tx.same_channel(&to_game_tx)
+            });
+        });
+        game_result
+    })
+}
+
+async fn restore_game(games: &Games, config: &Arc<ServerConfig>, game_save: GameSave) {
+    let game_name = game_save.game_name.clone();
+    println!(
+        "Restoring Game `{}` at turn #{}, waiting for the Players to reconnect",
+        game_name, game_save.turns
+    );
+    // They take back their Players with the saved credentials
+    let bots = game_save
+        .players
+        .iter()
+        .filter_map(|saved_player| {
+            let bot_name = saved_player.bot.clone()?;
+            Some((
+                saved_player.player_uuid,
+                bot_name,
+                saved_player.token.clone(),
+            ))
+        })
+        .collect::<Vec<(Uuid, String, String)>>();
+    let (to_game_tx, to_game_rx) = mpsc::channel::<PlayerAction>(1024);
+    let drawer = Drawer::new(&config.output_dir, &game_name).await;
+    let recorder = ReplayRecorder::resume(&config.output_dir, &game_name, game_save.turns).await;
+    let saver = GameSaver::new(&config.output_dir, &game_name);
+    let snapshotter = match &config.snapshots {
+        Some(output) => Some(Snapshotter::resume(&config.output_dir, &game_name, output).await),
+        None => None,
+    };
+    let game = Game::restore(
+        game_save,
+        to_game_rx,
+        Some(drawer),
+        Some(recorder),
+        Some(saver),
+        snapshotter,
+    )
+    .await;
+    spawn_game(games, game, to_game_tx);
+    for (player_uuid, bot_name, token) in bots.iter() {
+        launch_stdio_bot(
+            games,
+            config,
+            &game_name,
+            bot_name,
+            *player_uuid,
+            Some(token),
+        );
+    }
+}
+
+/// Writes `<output_dir>/<game_name>.map`, to play the same map again
+async fn export_map(output_dir: &Path, game_name: &str, map: &Map) {
+    let file_name = output_dir.join(format!("{}.map", game_name));
+    if let Err(err) = tokio::fs::write(&file_name, map.to_map_file()).await {
+        eprintln!("Unable to write map file `{:?}`: `{}`", file_name, err);
+    }
+}
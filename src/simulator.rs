@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use futures::executor::block_on;
+use tokio::sync::mpsc::{self, Receiver};
+use uuid::Uuid;
+
+use crate::{
+    game::{Action, Game, GameResult, GameSettings, MsgToPlayer, MsgToPlayerWithGameContent},
+    ruleset::Ruleset,
+};
+
+/// A BOT playing in the same process, without networking or timers
+pub trait Bot {
+    fn act(&mut self, observation: &Observation) -> Action;
+}
+
+/// What a Player knows at the start of a turn, the same as over TCP
+#[derive(Debug, Default)]
+pub struct Observation {
+    pub turn: u32,
+    /// Answer to the previous Action, `None` on the first turn
+    pub content: Option<MsgToPlayerWithGameContent>,
+    /// A Swapshroom moved the Player after `content` was sent
+    pub swapped: bool,
+}
+
+struct SimulatedPlayer {
+    player_uuid: Uuid,
+    bot: Box<dyn Bot>,
+    to_player_rx: Receiver<String>,
+    observation: Observation,
+}
+
+/// Runs a Game turn by turn synchronously, asking the Bots for their Actions
+pub struct Simulator {
+    game: Game,
+    players: Vec<SimulatedPlayer>,
+}
+
+impl Simulator {
+    /// The Bots join in the given order, `number_of_players` is set to their count
+    pub fn new(
+        mut game_settings: GameSettings,
+        ruleset: Ruleset,
+        bots: Vec<(String, Box<dyn Bot>)>,
+    ) -> Result<Self, String> {
+        if game_settings.max_turns.is_none() && game_settings.target_score.is_none() {
+            return Err("A simulated Game needs `max_turns` or `target_score` to end".to_string());
+        }
+        game_settings.number_of_players = bots.len() as u32;
+        game_settings.validate()?;
+        ruleset.validate()?;
+        let (_to_game_tx, to_game_rx) = mpsc::channel(1);
+        let mut game = block_on(Game::new(
+            "Simulation".to_string(),
+            to_game_rx,
+            game_settings,
+            ruleset,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ));
+
+        let mut players = Vec::with_capacity(bots.len());
+        for (i, (player_name, bot)) in bots.into_iter().enumerate() {
+            let player_uuid = Uuid::from_u128(i as u128 + 1);
+            let Some(pos) = game.free_spot() else {
+                return Err(format!(
+                    "No free spots left in the map for Bot `{}`",
+                    player_name
+                ));
+            };
+            // Large enough for the messages of a turn, it is drained after each one
+            let (to_player_tx, to_player_rx) = mpsc::channel::<String>(64);
+            game.add_player(player_uuid, player_name, to_player_tx, pos);
+            players.push(SimulatedPlayer {
+                player_uuid,
+                bot,
+                to_player_rx,
+                observation: Observation::default(),
+            });
+        }
+        Ok(Self { game, players })
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// What each Bot will observe in the next turn, in the order they joined
+    pub fn observations(&self) -> impl Iterator<Item = &Observation> {
+        self.players.iter().map(|player| &player.observation)
+    }
+
+    /// Plays one turn, returns the result if the Game is over after it
+    pub fn step(&mut self) -> Option<GameResult> {
+        let player_actions = self
+            .players
+            .iter_mut()
+            .map(|player| (player.player_uuid, player.bot.act(&player.observation)))
+            .collect::<BTreeMap<Uuid, Action>>();
+        block_on(self.game.play_turn(player_actions));
+
+        let turn = self.game.turns();
+        for player in self.players.iter_mut() {
+            player.observation.turn = turn;
+            player.observation.swapped = false;
+            while let Ok(msg) = player.to_player_rx.try_recv() {
+                if let Ok(content) = serde_json::from_str::<MsgToPlayerWithGameContent>(&msg) {
+                    player.observation.content = Some(content);
+                } else if let Ok(MsgToPlayer::Swapped) = serde_json::from_str::<MsgToPlayer>(&msg) {
+                    player.observation.swapped = true;
+                }
+            }
+        }
+
+        let reason = self.game.check_end_conditions()?;
+        Some(block_on(self.game.game_over(reason)))
+    }
+
+    pub fn run(mut self) -> GameResult {
+        loop {
+            if let Some(game_result) = self.step() {
+                return game_result;
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+use crate::{cell::Cell, harvest::Harvest, map::Topology, pos::Pos, seed::Seed};
+
+/// Where the JSON snapshots of the Games are written, one per turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SnapshotOutput {
+    /// `<output_dir>/<game_name>.snapshots`, one JSON line per turn
+    File,
+    /// `<output_dir>/<game_name>.snapshots/<turn>.json`,
+    /// only the last `keep` (at least 1) turns are kept if it is set
+    Directory { keep: Option<u32> },
+}
+
+/// The full state of a Game after a turn, for the tools reading the Games
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub game_name: String,
+    pub turn: u32,
+    pub width: usize,
+    pub height: usize,
+    pub topology: Topology,
+    /// Rows of the map, indexed by `[y][x]`
+    pub map: Vec<Vec<Cell>>,
+    /// In join order
+    pub players: Vec<PlayerState>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub player_name: String,
+    pub pos: Pos,
+    /// Sorted, so the same state is always the same JSON
+    pub harvests: BTreeMap<Harvest, u32>,
+    pub seeds: BTreeMap<Seed, u32>,
+    pub points: u32,
+    pub connected: bool,
+}
+
+pub enum Snapshotter {
+    File(File),
+    Directory { dir: PathBuf, keep: Option<u32> },
+}
+
+impl Snapshotter {
+    pub async fn new(output_dir: &Path, game_name: &str, output: &SnapshotOutput) -> Self {
+        Self::open(output_dir, game_name, output, false).await
+    }
+
+    /// Continues the snapshots of a restored Game
+    pub async fn resume(output_dir: &Path, game_name: &str, output: &SnapshotOutput) -> Self {
+        Self::open(output_dir, game_name, output, true).await
+    }
+
+    async fn open(
+        output_dir: &Path,
+        game_name: &str,
+        output: &SnapshotOutput,
+        resume: bool,
+    ) -> Self {
+        let file_name = output_dir.join(format!("{}.snapshots", game_name));
+        match output {
+            SnapshotOutput::File => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(resume)
+                    .write(true)
+                    .truncate(!resume)
+                    .open(&file_name)
+                    .await
+                    .unwrap_or_else(|_| panic!("Unable to Open file: {:?}", file_name));
+                Self::File(file)
+            }
+            SnapshotOutput::Directory { keep } => {
+                // The snapshots of a previous Game with the same name are outdated
+                if !resume && tokio::fs::metadata(&file_name).await.is_ok() {
+                    if let Err(err) = tokio::fs::remove_dir_all(&file_name).await {
+                        eprintln!("Unable to clear directory {:?}: `{}`", file_name, err);
+                    }
+                }
+                tokio::fs::create_dir_all(&file_name)
+                    .await
+                    .unwrap_or_else(|_| panic!("Unable to create directory: {:?}", file_name));
+                Self::Directory {
+                    dir: file_name,
+                    keep: *keep,
+                }
+            }
+        }
+    }
+
+    pub async fn write(&mut self, snapshot: &GameSnapshot) {
+        let mut content = match serde_json::to_string(snapshot) {
+            Ok(content) => content,
+            Err(err) => {
+                eprintln!(
+                    "Unable to serialize the snapshot of turn #{}: `{}`",
+                    snapshot.turn, err
+                );
+                return;
+            }
+        };
+        match self {
+            Self::File(file) => {
+                content.push('\n');
+                if let Err(err) = file.write_all(content.as_bytes()).await {
+                    eprintln!("Unable to write to snapshot file: `{}`", err);
+                }
+                if let Err(err) = file.flush().await {
+                    eprintln!("Unable to flush the snapshot file: `{}`", err);
+                }
+            }
+            Self::Directory { dir, keep } => {
+                let file_name = dir.join(snapshot_file_name(snapshot.turn));
+                if let Err(err) = tokio::fs::write(&file_name, content).await {
+                    eprintln!("Unable to write snapshot file `{:?}`: `{}`", file_name, err);
+                }
+                if let Some(outdated) = keep.and_then(|keep| snapshot.turn.checked_sub(keep)) {
+                    // Missing if the Game was restored in between
+                    let _ = tokio::fs::remove_file(dir.join(snapshot_file_name(outdated))).await;
+                }
+            }
+        }
+    }
+}
+
+/// Zero padded, so the files are sorted by turn
+fn snapshot_file_name(turn: u32) -> String {
+    format!("{:06}.json", turn)
+}
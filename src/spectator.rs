@@ -0,0 +1,59 @@
+use serde::Serialize;
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    cell::Cell,
+    game::{GameResult, GameSettings},
+    pos::Pos,
+    ruleset::Ruleset,
+};
+
+#[derive(Debug, Serialize)]
+pub enum MsgToSpectator {
+    Spectating {
+        game_settings: GameSettings,
+        ruleset: Box<Ruleset>,
+    },
+    Turn(TurnSnapshot),
+    GameOver(GameResult),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnSnapshot {
+    pub turn: u32,
+    /// Rows of the map, indexed by `[y][x]`
+    pub map: Vec<Vec<Cell>>,
+    pub players: Vec<PlayerSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerSnapshot {
+    pub player_name: String,
+    pub pos: Pos,
+    pub points: u32,
+    pub connected: bool,
+}
+
+/// Never waits for a slow Spectator, they rather miss a turn,
+/// the closed connections are dropped
+pub fn send_to_spectators(spectators: &mut Vec<Sender<String>>, msg_to_spectator: &MsgToSpectator) {
+    spectators.retain(|to_spectator_tx| !to_spectator_tx.is_closed());
+    if spectators.is_empty() {
+        return;
+    }
+    let msg = match serde_json::to_string(msg_to_spectator) {
+        Ok(msg) => msg,
+        Err(err) => {
+            eprintln!(
+                "Unable to serialize Message `{:?}` to Spectator: `{}`",
+                msg_to_spectator, err
+            );
+            return;
+        }
+    };
+    for to_spectator_tx in spectators.iter() {
+        if let Err(err) = to_spectator_tx.try_send(msg.clone()) {
+            eprintln!("Unable to send Msg to Spectator: `{}`", err);
+        }
+    }
+}
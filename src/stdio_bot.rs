@@ -0,0 +1,160 @@
+use std::{process::Stdio, sync::Arc, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    io::{duplex, DuplexStream},
+    process::{Child, Command},
+    time::Instant,
+};
+use tokio_util::codec::{Framed, FramedRead, FramedWrite, LinesCodec};
+use uuid::Uuid;
+
+use crate::{config::ServerConfig, handle_connection::handle_connection, server::Games};
+
+/// Turns in a row a BOT can leave without an Action before it is stopped
+const MAX_MISSED_TURNS: u32 = 3;
+
+/// Starts a BOT of the `ServerConfig` for the Game, its stdin/stdout speak the same
+/// JSON line protocol as a TCP connection, it has to join the Game found in the
+/// `FARMIO_GAME`, `FARMIO_PLAYER_NAME` and `FARMIO_PLAYER_UUID` environment variables,
+/// with the `FARMIO_PLAYER_TOKEN` too when it takes back its Player in a restored Game
+pub fn launch_stdio_bot(
+    games: &Games,
+    config: &Arc<ServerConfig>,
+    game_name: &str,
+    bot_name: &str,
+    player_uuid: Uuid,
+    token: Option<&str>,
+) {
+    let Some(program) = config.bots.get(bot_name) else {
+        eprintln!("Unknown BOT `{}` for Game `{}`", bot_name, game_name);
+        return;
+    };
+    let mut command = Command::new(&program[0]);
+    command
+        .args(&program[1..])
+        .env("FARMIO_GAME", game_name)
+        .env("FARMIO_PLAYER_NAME", bot_name)
+        .env("FARMIO_PLAYER_UUID", player_uuid.to_string());
+    if let Some(token) = token {
+        command.env("FARMIO_PLAYER_TOKEN", token);
+    }
+    let child = match command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            eprintln!(
+                "Unable to start BOT `{}` for Game `{}`: `{}`",
+                bot_name, game_name, err
+            );
+            return;
+        }
+    };
+    println!("BOT `{}` started for Game `{}`", bot_name, game_name);
+
+    // The BOT is a connection like the TCP ones, through an in-memory pipe
+    let (connection, bridge_connection) = duplex(64 * 1024);
+    let games = games.clone();
+    let timeout = Duration::from_millis(config.bot_timeout_ms);
+    let config = config.clone();
+    tokio::spawn(async move {
+        handle_connection(Framed::new(connection, LinesCodec::new()), games, config).await
+    });
+
+    let bot_name = bot_name.to_string();
+    tokio::spawn(async move { bridge(child, bridge_connection, bot_name, timeout).await });
+}
+
+/// Copies the lines between the BOT and its connection, until the BOT exits or stops reading,
+/// or the Game is over, then closing the connection disconnects the BOT from the Game.
+/// A BOT which does not answer in time idles that turn, like a slow TCP Player,
+/// and is stopped after `MAX_MISSED_TURNS` of them in a row
+async fn bridge(mut child: Child, connection: DuplexStream, bot_name: String, timeout: Duration) {
+    let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        unreachable!()
+    };
+    let mut bot_rx = FramedRead::new(stdout, LinesCodec::new());
+    let mut bot_tx = FramedWrite::new(stdin, LinesCodec::new());
+    let (mut connection_tx, mut connection_rx) = Framed::new(connection, LinesCodec::new()).split();
+    // Set while the Game waits for an Action of the BOT
+    let mut deadline: Option<Instant> = None;
+    let mut missed_turns = 0;
+
+    loop {
+        let timed_out = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            line = bot_rx.next() => match line {
+                Some(Ok(line)) => {
+                    deadline = None;
+                    missed_turns = 0;
+                    if connection_tx.send(line).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Err(err)) => {
+                    eprintln!("Invalid output of BOT `{}`: `{}`", bot_name, err);
+                    break;
+                }
+                None => {
+                    println!("BOT `{}` exited", bot_name);
+                    break;
+                }
+            },
+            line = connection_rx.next() => match line {
+                Some(Ok(line)) => {
+                    let waits = waits_for_action(&line);
+                    if waits == Some(true) && deadline.is_none() {
+                        deadline = Some(Instant::now() + timeout);
+                    }
+                    match tokio::time::timeout(timeout, bot_tx.send(line)).await {
+                        Ok(Ok(())) => {}
+                        _ => {
+                            eprintln!("BOT `{}` does not read its input", bot_name);
+                            break;
+                        }
+                    }
+                    // The Game is over, the BOT has nothing left to play
+                    if waits == Some(false) {
+                        println!("BOT `{}` stopped at the end of the Game", bot_name);
+                        break;
+                    }
+                }
+                _ => break,
+            },
+            _ = timed_out => {
+                deadline = None;
+                missed_turns += 1;
+                if missed_turns >= MAX_MISSED_TURNS {
+                    eprintln!("BOT `{}` missed {} turns in a row, it is stopped", bot_name, missed_turns);
+                    break;
+                }
+                eprintln!("BOT `{}` did not answer in {:?}, it idles", bot_name, timeout);
+            }
+        }
+    }
+
+    if let Err(err) = child.kill().await {
+        eprintln!("Unable to stop BOT `{}`: `{}`", bot_name, err);
+    }
+}
+
+/// `Some(true)` if the BOT has to answer the message with an Action,
+/// `Some(false)` if it does not have to act anymore
+fn waits_for_action(msg: &str) -> Option<bool> {
+    match serde_json::from_str::<serde_json::Value>(msg) {
+        Ok(serde_json::Value::String(msg)) if msg == "GameStarted" => Some(true),
+        Ok(serde_json::Value::Object(msg)) if msg.contains_key("TurnStarted") => Some(true),
+        Ok(serde_json::Value::Object(msg)) if msg.contains_key("result") => Some(true),
+        Ok(serde_json::Value::Object(msg)) if msg.contains_key("GameOver") => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,541 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use tokio::process::{Child, Command};
+use uuid::Uuid;
+
+use crate::{
+    config::ServerConfig,
+    game::{GamePhase, GameResult, GameSettings, PlayerResult},
+    map::Map,
+    ruleset::Ruleset,
+    server::{accept_connections, bind, create_game, Games},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentBot {
+    pub name: String,
+    /// Program and its arguments, it finds the Game in the `FARMIO_*` environment variables
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TournamentFormat {
+    /// Every group of `players_per_game` Bots plays once
+    RoundRobin,
+    /// Each round the Bots with similar ratings, who did not meet yet, play each other
+    Swiss { rounds: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TournamentConfig {
+    pub bots: Vec<TournamentBot>,
+    pub format: TournamentFormat,
+    pub players_per_game: u32,
+    /// Every pairing is played on each seed and map size
+    pub seeds: Vec<u64>,
+    pub map_sizes: Vec<u32>,
+    /// `number_of_players`, `seed` and `map_size` are set for each Game
+    pub game_settings: GameSettings,
+    pub ratings_file: PathBuf,
+    pub k_factor: f64,
+    /// The Game is cancelled if the Bots do not join in time
+    pub join_timeout_ms: u64,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            bots: Vec::new(),
+            format: TournamentFormat::RoundRobin,
+            players_per_game: 2,
+            seeds: vec![0],
+            map_sizes: vec![16],
+            game_settings: GameSettings {
+                max_turns: Some(100),
+                ..GameSettings::default()
+            },
+            ratings_file: PathBuf::from("ratings.json"),
+            k_factor: 32.0,
+            join_timeout_ms: 10_000,
+        }
+    }
+}
+
+impl TournamentConfig {
+    pub fn load(path: &Path) -> Self {
+        let content = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("Unable to read tournament file `{:?}`: `{}`", path, err));
+        let tournament: Self = serde_json::from_str(&content)
+            .unwrap_or_else(|err| panic!("Invalid tournament file `{:?}`: `{}`", path, err));
+        if tournament.players_per_game < 2
+            || tournament.bots.len() < tournament.players_per_game as usize
+        {
+            panic!("A tournament needs at least `players_per_game` (>= 2) Bots!");
+        }
+        if tournament.bots.iter().any(|bot| bot.command.is_empty()) {
+            panic!("Every Bot needs a `command`!");
+        }
+        let names = tournament
+            .bots
+            .iter()
+            .map(|bot| &bot.name)
+            .collect::<BTreeSet<_>>();
+        if names.len() != tournament.bots.len() {
+            panic!("The Bots need unique names, the ratings are kept by them!");
+        }
+        if tournament.game_settings.max_turns.is_none() {
+            panic!("Tournament Games need `max_turns` to end!");
+        }
+        for map_size in tournament.map_sizes.iter() {
+            let game_settings = GameSettings {
+                number_of_players: tournament.players_per_game,
+                map_size: *map_size,
+                ..tournament.game_settings.clone()
+            };
+            if let Err(err) = game_settings.validate() {
+                panic!("Invalid `game_settings` in tournament file: {}", err);
+            }
+        }
+        tournament
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rating {
+    pub rating: f64,
+    pub games: u32,
+    /// Ranked first alone
+    pub wins: u32,
+    /// Ranked first with others
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: 1500.0,
+            games: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            points: 0,
+        }
+    }
+}
+
+/// Ratings by Bot name, kept between the tournaments
+pub type Ratings = BTreeMap<String, Rating>;
+
+fn load_ratings(ratings_file: &Path) -> Ratings {
+    match std::fs::read_to_string(ratings_file) {
+        Ok(content) => serde_json::from_str(&content)
+            .unwrap_or_else(|err| panic!("Invalid ratings file `{:?}`: `{}`", ratings_file, err)),
+        Err(_) => Ratings::new(),
+    }
+}
+
+fn save_ratings(ratings_file: &Path, ratings: &Ratings) {
+    let result = serde_json::to_string_pretty(ratings)
+        .map_err(std::io::Error::from)
+        .and_then(|content| std::fs::write(ratings_file, content));
+    if let Err(err) = result {
+        eprintln!(
+            "Unable to save the ratings to `{:?}`: `{}`",
+            ratings_file, err
+        );
+    }
+}
+
+/// Multiplayer Elo: every pair of Players of the Game counts as a match between them,
+/// the Players are matched to the Bots by the `FARMIO_PLAYER_UUID` they were launched with
+fn update_ratings(
+    ratings: &mut Ratings,
+    game_result: &GameResult,
+    launched: &BTreeMap<Uuid, String>,
+    k_factor: f64,
+) {
+    let mut ranking = Vec::<(&str, &PlayerResult)>::with_capacity(launched.len());
+    for player_result in game_result.ranking.iter() {
+        match launched.get(&player_result.player_uuid) {
+            Some(bot_name) => ranking.push((bot_name, player_result)),
+            None => eprintln!(
+                "Player `{}` was not launched by the tournament, it is not rated",
+                player_result.player_name
+            ),
+        }
+    }
+    let before = ranking
+        .iter()
+        .map(|(bot_name, _)| {
+            ratings
+                .get(*bot_name)
+                .map_or(1500.0, |rating| rating.rating)
+        })
+        .collect::<Vec<f64>>();
+    let opponents = (ranking.len().max(2) - 1) as f64;
+    let best_rank = ranking.iter().map(|(_, r)| r.rank).min().unwrap_or(1);
+    let shared_first = ranking.iter().filter(|(_, r)| r.rank == best_rank).count() > 1;
+
+    for (i, (bot_name, player_result)) in ranking.iter().enumerate() {
+        let mut delta = 0.0;
+        for (j, (_, other_result)) in ranking.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let score = match player_result.rank.cmp(&other_result.rank) {
+                std::cmp::Ordering::Less => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Greater => 0.0,
+            };
+            let expected = 1.0 / (1.0 + 10f64.powf((before[j] - before[i]) / 400.0));
+            delta += score - expected;
+        }
+
+        let rating = ratings.entry(bot_name.to_string()).or_default();
+        rating.rating += k_factor * delta / opponents;
+        rating.games += 1;
+        rating.points += player_result.points as u64;
+        match (player_result.rank == best_rank, shared_first) {
+            (true, false) => rating.wins += 1,
+            (true, true) => rating.draws += 1,
+            _ => rating.losses += 1,
+        }
+    }
+}
+
+/// Every combination of `group_size` Bots, by their index in the roster
+fn round_robin_groups(bots: usize, group_size: usize) -> Vec<Vec<usize>> {
+    let mut groups = Vec::new();
+    add_groups(
+        0,
+        bots,
+        group_size,
+        &mut Vec::with_capacity(group_size),
+        &mut groups,
+    );
+    groups
+}
+
+fn add_groups(
+    start: usize,
+    bots: usize,
+    group_size: usize,
+    group: &mut Vec<usize>,
+    groups: &mut Vec<Vec<usize>>,
+) {
+    if group.len() == group_size {
+        groups.push(group.clone());
+        return;
+    }
+    for i in start..bots {
+        group.push(i);
+        add_groups(i + 1, bots, group_size, group, groups);
+        group.pop();
+    }
+}
+
+/// The earlier rounds of a Swiss tournament, by the index of the Bots in the roster
+#[derive(Debug, Default)]
+struct SwissHistory {
+    /// How many times two Bots played each other, the lower index first
+    met: BTreeMap<(usize, usize), u32>,
+    /// How many rounds each Bot sat out
+    byes: BTreeMap<usize, u32>,
+}
+
+impl SwissHistory {
+    fn met(&self, a: usize, b: usize) -> u32 {
+        self.met.get(&(a.min(b), a.max(b))).copied().unwrap_or(0)
+    }
+
+    /// How many opponents the Bot had, counting the repeated ones too
+    fn opponents(&self, bot: usize) -> u32 {
+        self.met
+            .iter()
+            .filter(|((a, b), _)| *a == bot || *b == bot)
+            .map(|(_, met)| met)
+            .sum()
+    }
+
+    fn record(&mut self, bots: usize, groups: &[Vec<usize>]) {
+        for group in groups {
+            for (i, a) in group.iter().enumerate() {
+                for b in group[i + 1..].iter() {
+                    *self.met.entry((*a.min(b), *a.max(b))).or_default() += 1;
+                }
+            }
+        }
+        for bot in 0..bots {
+            if !groups.iter().any(|group| group.contains(&bot)) {
+                *self.byes.entry(bot).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// The Bots sorted by rating, each group is filled with the closest rated Bots who met
+/// the group the least, the Bots who sat out the least rounds and had the most opponents
+/// sit out, the lowest rated first
+fn swiss_groups(
+    bots: &[TournamentBot],
+    ratings: &Ratings,
+    group_size: usize,
+    history: &SwissHistory,
+) -> Vec<Vec<usize>> {
+    let rating = |i: &usize| ratings.get(&bots[*i].name).map_or(1500.0, |r| r.rating);
+    let byes = |i: &usize| history.byes.get(i).copied().unwrap_or(0);
+    let mut order = (0..bots.len()).collect::<Vec<usize>>();
+    order.sort_by(|a, b| rating(b).total_cmp(&rating(a)));
+
+    let mut sitting_out = order.clone();
+    sitting_out.sort_by(|a, b| {
+        byes(a)
+            .cmp(&byes(b))
+            .then(history.opponents(*b).cmp(&history.opponents(*a)))
+            .then(rating(a).total_cmp(&rating(b)))
+    });
+    sitting_out.truncate(bots.len() % group_size);
+    order.retain(|i| !sitting_out.contains(i));
+
+    let mut groups = Vec::with_capacity(order.len() / group_size);
+    while !order.is_empty() {
+        let mut group = vec![order.remove(0)];
+        while group.len() < group_size {
+            let (next, _) = order
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, i)| group.iter().map(|g| history.met(*g, **i)).sum::<u32>())
+                .expect("The Bots are a multiple of the group size");
+            group.push(order.remove(next));
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+fn standings(ratings: &Ratings, bots: &[TournamentBot]) -> String {
+    let mut rows = bots
+        .iter()
+        .map(|bot| {
+            (
+                bot.name.as_str(),
+                ratings.get(&bot.name).cloned().unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<(&str, Rating)>>();
+    rows.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating));
+
+    let mut report = format!(
+        "{:>4}  {:<24} {:>8} {:>6} {:>5} {:>5} {:>5} {:>10}\n",
+        "Rank", "Bot", "Rating", "Games", "W", "D", "L", "Avg points"
+    );
+    for (i, (name, rating)) in rows.iter().enumerate() {
+        let avg_points = match rating.games {
+            0 => 0.0,
+            games => rating.points as f64 / games as f64,
+        };
+        let _ = writeln!(
+            report,
+            "{:>4}  {:<24} {:>8.1} {:>6} {:>5} {:>5} {:>5} {:>10.1}",
+            i + 1,
+            name,
+            rating.rating,
+            rating.games,
+            rating.wins,
+            rating.draws,
+            rating.losses,
+            avg_points
+        );
+    }
+    report
+}
+
+/// Hosts the Games of the tournament one by one, the Bots connect to them over TCP
+pub async fn run_tournament(config: ServerConfig, tournament: TournamentConfig) {
+    let listener = bind(&config).await;
+    let config = Arc::new(config);
+    let games: Games = Arc::new(DashMap::new());
+    tokio::spawn(accept_connections(listener, games.clone(), config.clone()));
+
+    let ruleset = config
+        .load_ruleset(&tournament.game_settings)
+        .unwrap_or_else(|err| panic!("Invalid ruleset for the tournament: {}", err));
+    let map = config
+        .load_map(
+            &GameSettings {
+                number_of_players: tournament.players_per_game,
+                ..tournament.game_settings.clone()
+            },
+            &ruleset,
+        )
+        .unwrap_or_else(|err| panic!("Invalid map for the tournament: {}", err));
+    // A map file comes in one size only
+    let map_sizes = match &map {
+        Some(map) => vec![map.width() as u32],
+        None => tournament.map_sizes.clone(),
+    };
+    let mut ratings = load_ratings(&tournament.ratings_file);
+    let group_size = tournament.players_per_game as usize;
+    let rounds = match tournament.format {
+        TournamentFormat::RoundRobin => 1,
+        TournamentFormat::Swiss { rounds } => rounds,
+    };
+    let mut game_number = 0;
+    let mut swiss_history = SwissHistory::default();
+
+    for round in 1..=rounds {
+        let groups = match tournament.format {
+            TournamentFormat::RoundRobin => round_robin_groups(tournament.bots.len(), group_size),
+            TournamentFormat::Swiss { .. } => {
+                swiss_groups(&tournament.bots, &ratings, group_size, &swiss_history)
+            }
+        };
+        swiss_history.record(tournament.bots.len(), &groups);
+        println!(
+            "Tournament round {}/{}: {} groups",
+            round,
+            rounds,
+            groups.len()
+        );
+
+        for group in groups {
+            let group = group
+                .iter()
+                .map(|i| &tournament.bots[*i])
+                .collect::<Vec<_>>();
+            for seed in tournament.seeds.iter() {
+                for map_size in map_sizes.iter() {
+                    game_number += 1;
+                    let game_name = format!("tournament-{:04}", game_number);
+                    let game_settings = GameSettings {
+                        number_of_players: tournament.players_per_game,
+                        seed: *seed,
+                        map_size: *map_size,
+                        ..tournament.game_settings.clone()
+                    };
+                    let game_result = play_game(
+                        &games,
+                        &config,
+                        &tournament,
+                        &group,
+                        &game_name,
+                        game_settings,
+                        &ruleset,
+                        &map,
+                    )
+                    .await;
+                    if let Some((game_result, launched)) = game_result {
+                        update_ratings(&mut ratings, &game_result, &launched, tournament.k_factor);
+                        save_ratings(&tournament.ratings_file, &ratings);
+                    }
+                }
+            }
+        }
+    }
+
+    let report = standings(&ratings, &tournament.bots);
+    println!("{}", report);
+    let standings_file = config.output_dir.join("standings.txt");
+    if let Err(err) = std::fs::write(&standings_file, report) {
+        eprintln!(
+            "Unable to write the standings to `{:?}`: `{}`",
+            standings_file, err
+        );
+    }
+}
+
+/// Returns `None` if the Game was cancelled, the names of the Bots by their UUID otherwise
+#[allow(clippy::too_many_arguments)]
+async fn play_game(
+    games: &Games,
+    config: &Arc<ServerConfig>,
+    tournament: &TournamentConfig,
+    group: &[&TournamentBot],
+    game_name: &str,
+    game_settings: GameSettings,
+    ruleset: &Ruleset,
+    map: &Option<Map>,
+) -> Option<(GameResult, BTreeMap<Uuid, String>)> {
+    let names = group
+        .iter()
+        .map(|bot| bot.name.as_str())
+        .collect::<Vec<&str>>();
+    println!(
+        "Tournament Game `{}`: {:?} ({:?})",
+        game_name, names, game_settings
+    );
+    let (_to_game_tx, game_handle) = create_game(
+        games,
+        config,
+        game_name,
+        game_settings,
+        ruleset.clone(),
+        map.clone(),
+    )
+    .await;
+    let mut status = games.get(game_name)?.status.clone();
+
+    // Killed when dropped, at the end of the Game
+    let mut children = Vec::<Child>::with_capacity(group.len());
+    let mut launched = BTreeMap::<Uuid, String>::new();
+    for bot in group {
+        let player_uuid = Uuid::new_v4();
+        match Command::new(&bot.command[0])
+            .args(&bot.command[1..])
+            .env("FARMIO_ADDR", config.ip_port())
+            .env("FARMIO_GAME", game_name)
+            .env("FARMIO_PLAYER_NAME", &bot.name)
+            .env("FARMIO_PLAYER_UUID", player_uuid.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(child) => {
+                children.push(child);
+                launched.insert(player_uuid, bot.name.clone());
+            }
+            Err(err) => eprintln!("Unable to start Bot `{}`: `{}`", bot.name, err),
+        }
+    }
+
+    let join_timeout = Duration::from_millis(tournament.join_timeout_ms);
+    // The borrowed status is released at once, it would block the Game
+    let started = matches!(
+        tokio::time::timeout(
+            join_timeout,
+            status.wait_for(|status| status.phase != GamePhase::Waiting),
+        )
+        .await,
+        Ok(Ok(_))
+    );
+    if !started {
+        eprintln!(
+            "Tournament Game `{}` is cancelled, the Bots did not join in time",
+            game_name
+        );
+        game_handle.abort();
+        games.remove(game_name);
+        return None;
+    }
+
+    match game_handle.await {
+        Ok(game_result) => Some((game_result, launched)),
+        Err(err) => {
+            eprintln!("Tournament Game `{}` failed: `{}`", game_name, err);
+            None
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Spectates the Games over the WebSocket listener, in a browser
+const VIEWER: &str = include_str!("../viewer/index.html");
+
+/// Longest request head read, the viewer only needs the request line
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+/// A client sending its request head slower is dropped, so it can not hold the connection
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves the viewer over HTTP, `/?game=<game_name>` watches a Game,
+/// `/` lists the running Games
+pub async fn accept_http_connections(listener: TcpListener, websocket_port: u16) {
+    let viewer = Arc::new(VIEWER.replace("{{websocket_port}}", &websocket_port.to_string()));
+    while let Ok((socket, addr)) = listener.accept().await {
+        let viewer = viewer.clone();
+        tokio::spawn(async move {
+            if let Err(err) = serve(socket, &viewer).await {
+                eprintln!("Unable to serve the viewer to `{}`: `{}`", addr, err);
+            }
+        });
+    }
+    unreachable!()
+}
+
+async fn serve(mut socket: TcpStream, viewer: &str) -> std::io::Result<()> {
+    let Some(request) =
+        tokio::time::timeout(REQUEST_TIMEOUT, read_request_head(&mut socket)).await??
+    else {
+        return Ok(());
+    };
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.split_whitespace();
+    let (method, path) = (request_line.next(), request_line.next().unwrap_or(""));
+    // The query only matters to the viewer
+    let path = path.split('?').next().unwrap_or("");
+
+    let (status, content_type, body) = match (method, path) {
+        (Some("GET"), "/" | "/index.html") => ("200 OK", "text/html; charset=utf-8", viewer),
+        (Some("GET"), _) => ("404 Not Found", "text/plain", "Not Found"),
+        _ => ("405 Method Not Allowed", "text/plain", "Method Not Allowed"),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await
+}
+
+/// `None` if the connection is closed or the head is too long
+async fn read_request_head(socket: &mut TcpStream) -> std::io::Result<Option<Vec<u8>>> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = socket.read(&mut buffer).await?;
+        if n == 0 || request.len() + n > MAX_REQUEST_SIZE {
+            return Ok(None);
+        }
+        request.extend_from_slice(&buffer[..n]);
+    }
+    Ok(Some(request))
+}
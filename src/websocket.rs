@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::{
+    io::{duplex, DuplexStream},
+    net::{TcpListener, TcpStream},
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+use tokio_util::codec::{Framed, LinesCodec};
+
+use crate::{
+    config::ServerConfig,
+    handle_connection::{handle_connection, LobbyToPlayer},
+    server::Games,
+};
+
+/// Each text message of a WebSocket is one message of the JSON protocol of the TCP connections,
+/// so browsers can play and spectate the same Games
+pub async fn accept_websocket_connections(
+    listener: TcpListener,
+    games: Games,
+    config: Arc<ServerConfig>,
+) {
+    while let Ok((socket, addr)) = listener.accept().await {
+        let games = games.clone();
+        let config = config.clone();
+        tokio::spawn(async move {
+            let websocket = match accept_async(socket).await {
+                Ok(websocket) => websocket,
+                Err(err) => {
+                    eprintln!("Unable to accept WebSocket from `{}`: `{}`", addr, err);
+                    return;
+                }
+            };
+            // The WebSocket is a connection like the TCP ones, through an in-memory pipe
+            let (connection, bridge_connection) = duplex(64 * 1024);
+            tokio::spawn(async move {
+                handle_connection(Framed::new(connection, LinesCodec::new()), games, config).await
+            });
+            bridge(websocket, bridge_connection).await;
+        });
+    }
+    unreachable!()
+}
+
+/// Copies the messages between the WebSocket and its connection, until either is closed,
+/// then closing the connection disconnects the Player from the Game
+async fn bridge(websocket: WebSocketStream<TcpStream>, connection: DuplexStream) {
+    let (mut websocket_tx, mut websocket_rx) = websocket.split();
+    let (mut connection_tx, mut connection_rx) = Framed::new(connection, LinesCodec::new()).split();
+
+    loop {
+        tokio::select! {
+            msg = websocket_rx.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    // Pretty printed JSON is one message too, its line breaks are dropped
+                    let line = match serde_json::from_str::<Value>(&text) {
+                        Ok(value) => value.to_string(),
+                        Err(err) => {
+                            eprintln!("Invalid JSON from WebSocket: `{}`", err);
+                            let invalid_msg = serde_json::json!(LobbyToPlayer::InvalidMsg);
+                            if websocket_tx
+                                .send(Message::text(invalid_msg.to_string()))
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                            continue;
+                        }
+                    };
+                    if connection_tx.send(line).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by tungstenite, binary messages are not part of the protocol
+                Some(Ok(_)) => {}
+            },
+            line = connection_rx.next() => match line {
+                Some(Ok(line)) => {
+                    if let Err(err) = websocket_tx.send(Message::text(line)).await {
+                        eprintln!("Unable to send Msg to WebSocket: `{}`", err);
+                        break;
+                    }
+                }
+                _ => break,
+            },
+        }
+    }
+}
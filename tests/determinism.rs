@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use farmio::{
+    direction::Direction,
+    game::{Action, Game, GameSettings},
+    ruleset::Ruleset,
+    seed::Seed,
+};
+use serde_json::Value;
+use tokio::sync::mpsc::{self, Receiver};
+use uuid::Uuid;
+
+const PLAYERS: u128 = 3;
+const TURNS: u32 = 200;
+
+/// The same for every run, it mixes moves into the same spots with the other Actions
+fn action(turn: u32, player: u128) -> Action {
+    let directions = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+    let seeds = [Seed::Wheat, Seed::Bush, Seed::Swapshroom, Seed::Wallbush];
+    let x = (turn as u128 * 7919 + player * 104_729) % 13;
+    match x {
+        0..=4 => Action::Move {
+            direction: directions[(x as usize + turn as usize) % 4].clone(),
+        },
+        5 | 6 => Action::Harvest,
+        7 | 8 => Action::Plant {
+            seed: seeds[(turn as usize) % 4].clone(),
+        },
+        9 => Action::Trade {
+            seed: seeds[(player as usize + turn as usize) % 4].clone(),
+            volume: 1,
+        },
+        10 => Action::Till,
+        11 => Action::Look,
+        _ => Action::Idle,
+    }
+}
+
+/// Every message the Players received, and the state of the Game at the end
+async fn play(game_settings: GameSettings) -> (Vec<Value>, Value, Vec<Value>) {
+    let (_to_game_tx, to_game_rx) = mpsc::channel(1);
+    let mut game = Game::new(
+        "Determinism".to_string(),
+        to_game_rx,
+        game_settings,
+        Ruleset::default(),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let mut to_player_rxs = Vec::<Receiver<String>>::new();
+    for i in 0..PLAYERS {
+        let pos = game.free_spot().unwrap();
+        let (to_player_tx, to_player_rx) = mpsc::channel::<String>(64);
+        game.add_player(Uuid::from_u128(i + 1), format!("P{}", i), to_player_tx, pos);
+        to_player_rxs.push(to_player_rx);
+    }
+
+    let mut msgs = Vec::new();
+    for turn in 0..TURNS {
+        let actions = (0..PLAYERS)
+            .map(|i| (Uuid::from_u128(i + 1), action(turn, i)))
+            .collect::<BTreeMap<Uuid, Action>>();
+        game.play_turn(actions).await;
+        for to_player_rx in to_player_rxs.iter_mut() {
+            while let Ok(msg) = to_player_rx.try_recv() {
+                // Inventories are maps, their JSON is compared regardless of key order
+                msgs.push(serde_json::from_str(&msg).unwrap());
+            }
+        }
+    }
+
+    let map = serde_json::to_value(game.map()).unwrap();
+    let players = game
+        .players()
+        .values()
+        .map(|player| {
+            serde_json::json!({
+                "pos": player.pos,
+                "points": player.points,
+                "harvests": player.harvests,
+                "seeds": player.seeds,
+            })
+        })
+        .collect();
+    (msgs, map, players)
+}
+
+#[tokio::test]
+async fn same_seed_and_actions_give_the_same_game() {
+    for seed in [0, 1, 42] {
+        let game_settings = GameSettings {
+            seed,
+            number_of_players: PLAYERS as u32,
+            map_size: 8,
+            starting_seeds: BTreeMap::from([
+                (Seed::Wheat, 20),
+                (Seed::Bush, 20),
+                (Seed::Swapshroom, 20),
+                (Seed::Wallbush, 20),
+            ]),
+            ..GameSettings::default()
+        };
+        let first = play(game_settings.clone()).await;
+        let second = play(game_settings).await;
+
+        assert!(!first.0.is_empty());
+        assert_eq!(first, second, "Game with seed {} differs", seed);
+    }
+}
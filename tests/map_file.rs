@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+
+use farmio::{
+    game::GameSettings,
+    ground::Ground,
+    map::{Map, Topology},
+    plant::Plant,
+    pos::Pos,
+    ruleset::Ruleset,
+};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+/// The rows of the map file, without its comments
+fn rows(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// A directory of its own for each test, the tests run in parallel
+fn maps_dir(test: &str) -> PathBuf {
+    let maps_dir = std::env::temp_dir().join(format!("farmio-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&maps_dir).unwrap();
+    maps_dir
+}
+
+#[test]
+fn parses_every_cell() {
+    let content = "\
+# A comment
+dW tB dT sC
+
+dP sI tH x.
+";
+    let map = Map::parse(content, &Ruleset::default()).unwrap();
+
+    assert_eq!((map.width(), map.height()), (4, 2));
+    assert!(matches!(map.cells()[0][0].plant, Plant::Wheat(_)));
+    assert!(matches!(map.cells()[0][3].ground, Ground::Sand));
+    assert!(matches!(map.cells()[1][2].plant, Plant::Wallbush(_)));
+    assert_eq!(
+        map.get_stones().into_iter().collect::<Vec<Pos>>(),
+        vec![Pos { x: 3, y: 1 }]
+    );
+}
+
+#[test]
+fn exports_the_parsed_map() {
+    let content = std::fs::read_to_string("maps/duel.map").unwrap();
+    let map = Map::parse(&content, &Ruleset::default()).unwrap();
+
+    assert_eq!(rows(&map.to_map_file()), rows(&content));
+}
+
+#[test]
+fn parses_the_exported_map() {
+    let ruleset = Ruleset::default();
+    let game_settings = GameSettings {
+        map_width: Some(12),
+        map_height: Some(7),
+        ..GameSettings::default()
+    };
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(7);
+    let map = Map::generate_map(&game_settings, &mut rng, &ruleset);
+
+    let exported = map.to_map_file();
+    let parsed = Map::parse(&exported, &ruleset).unwrap();
+    assert_eq!((parsed.width(), parsed.height()), (12, 7));
+    assert_eq!(parsed.get_stones(), map.get_stones());
+    assert_eq!(parsed.to_map_file(), exported);
+}
+
+#[test]
+fn rejects_unknown_cells() {
+    let ruleset = Ruleset::default();
+    for content in [
+        "dW dW\ndW qW",
+        "dW dW\ndW dX",
+        "dW dW\ndW dWW",
+        "dW dW\ndW d",
+    ] {
+        assert_eq!(
+            Map::parse(content, &ruleset).err(),
+            Some("Unknown Cell on line 2".to_string())
+        );
+    }
+}
+
+#[test]
+fn rejects_uneven_or_empty_maps() {
+    let ruleset = Ruleset::default();
+    for content in ["dW dW\ndW", "# Only a comment\n", ""] {
+        assert_eq!(
+            Map::parse(content, &ruleset).err(),
+            Some("The rows of the map have to be the same length".to_string())
+        );
+    }
+}
+
+#[test]
+fn loads_a_map_with_enough_stones() {
+    let maps_dir = maps_dir("enough-stones");
+    std::fs::write(maps_dir.join("two.map"), "x. dW\ndW x.\n").unwrap();
+
+    let map = Map::load(&maps_dir, "two", 2, &Ruleset::default()).unwrap();
+    assert_eq!(map.get_stones().len(), 2);
+    assert!(matches!(map.topology(), Topology::Torus));
+}
+
+#[test]
+fn rejects_too_few_stones() {
+    let maps_dir = maps_dir("too-few-stones");
+    std::fs::write(maps_dir.join("one.map"), "x. dW\ndW dW\n").unwrap();
+
+    assert_eq!(
+        Map::load(&maps_dir, "one", 2, &Ruleset::default()).err(),
+        Some("Map `one` has `1` Stones to start on, for `2` Players".to_string())
+    );
+}
+
+#[test]
+fn rejects_names_outside_the_maps_dir() {
+    let ruleset = Ruleset::default();
+    for name in ["../duel", "maps/duel", ""] {
+        assert!(Map::load(Path::new("maps"), name, 2, &ruleset).is_err());
+    }
+}
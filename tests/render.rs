@@ -0,0 +1,46 @@
+use farmio::{
+    map::Map,
+    pos::Pos,
+    render::{map_to_rgb, map_to_svg},
+    ruleset::Ruleset,
+};
+
+fn small_map(ruleset: &Ruleset) -> Map {
+    Map::parse("x. dW tB\nw. sC x.\n", ruleset).unwrap()
+}
+
+fn players() -> Vec<(Pos, String)> {
+    vec![
+        (Pos { x: 0, y: 0 }, "Ada".to_string()),
+        (Pos { x: 2, y: 1 }, "Bob".to_string()),
+    ]
+}
+
+#[test]
+fn renders_cell_size_pixels_per_cell() {
+    let ruleset = Ruleset::default();
+    let (width, height, pixels) =
+        map_to_rgb(&small_map(&ruleset), &players(), &ruleset, 8).unwrap();
+
+    assert_eq!((width, height), (24, 16));
+    assert_eq!(pixels.len(), 24 * 16 * 3);
+}
+
+#[test]
+fn renders_the_svg_at_the_same_size() {
+    let ruleset = Ruleset::default();
+    let svg = map_to_svg(&small_map(&ruleset), &players(), &ruleset, 8).unwrap();
+
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="16""#));
+    assert_eq!(svg.matches("<rect ").count(), 6);
+    assert!(svg.contains(">Ada</text>") && svg.contains(">Bob</text>"));
+}
+
+#[test]
+fn rejects_images_too_large() {
+    let ruleset = Ruleset::default();
+    let map = small_map(&ruleset);
+
+    assert!(map_to_rgb(&map, &players(), &ruleset, u32::MAX).is_err());
+    assert!(map_to_svg(&map, &players(), &ruleset, u32::MAX).is_err());
+}
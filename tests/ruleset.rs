@@ -0,0 +1,84 @@
+use std::{collections::BTreeMap, path::Path};
+
+use farmio::{
+    ruleset::{self, Ruleset},
+    seed::Seed,
+};
+
+fn invalid(ruleset: Ruleset) -> String {
+    ruleset.validate().unwrap_err()
+}
+
+#[test]
+fn accepts_the_bundled_rulesets() {
+    assert!(Ruleset::default().validate().is_ok());
+    for name in ["default", "fast"] {
+        assert!(ruleset::load(Path::new("rulesets"), name).is_ok());
+    }
+}
+
+#[test]
+fn rejects_unknown_rulesets() {
+    for name in ["missing", "../rulesets/fast", ""] {
+        assert!(ruleset::load(Path::new("rulesets"), name).is_err());
+    }
+}
+
+#[test]
+fn rejects_growths_overflowing() {
+    let mut ruleset = Ruleset::default();
+    ruleset.wheat.growth_to_grains = 254;
+    assert_eq!(invalid(ruleset), "The growth of `wheat` is too high");
+
+    // A Pumpkin grows up to 5 times `growth_to_pumpkinseed`
+    let mut ruleset = Ruleset::default();
+    ruleset.pumpkin.growth_to_pumpkinseed = 51;
+    assert_eq!(invalid(ruleset), "The growth of `pumpkin` is too high");
+}
+
+#[test]
+fn rejects_no_growth_per_harvest() {
+    let mut ruleset = Ruleset::default();
+    ruleset.cactus.growth_per_cactusmeat = 0;
+    assert_eq!(invalid(ruleset), "The growth per harvest can not be 0");
+
+    let mut ruleset = Ruleset::default();
+    ruleset.bush.growth_per_berries = 0;
+    assert_eq!(invalid(ruleset), "The growth per harvest can not be 0");
+}
+
+#[test]
+fn rejects_pumpkins_shrinking_below_zero() {
+    let mut ruleset = Ruleset::default();
+    ruleset.pumpkin.growth_to_pumpkinseed = 1;
+    assert_eq!(
+        invalid(ruleset),
+        "The `growth_to_pumpkinseed` can not be lower than the growth rate `2`"
+    );
+}
+
+#[test]
+fn rejects_berries_overflowing() {
+    let mut ruleset = Ruleset::default();
+    ruleset.bush.growth_per_berries = 1;
+    ruleset.bush.max_berries = 200;
+    assert_eq!(invalid(ruleset), "The berries of `bush` can grow too many");
+
+    // Not without a growth rate
+    let mut ruleset = Ruleset {
+        growth_rate: 0,
+        water_growth_rate: 0,
+        ..Ruleset::default()
+    };
+    ruleset.pumpkin.growth_to_pumpkinseed = 1;
+    ruleset.bush.growth_per_berries = 1;
+    ruleset.bush.max_berries = 200;
+    assert!(ruleset.validate().is_ok());
+}
+
+#[test]
+fn rejects_free_trades() {
+    let mut ruleset = Ruleset::default();
+    ruleset.trades.insert(Seed::Wheat, BTreeMap::new());
+    assert_eq!(invalid(ruleset), "A trade has to cost something");
+}
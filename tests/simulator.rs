@@ -0,0 +1,116 @@
+use farmio::{
+    direction::Direction,
+    game::{Action, GameOverReason, GameSettings, MsgToPlayer},
+    ruleset::Ruleset,
+    simulator::{Bot, Observation, Simulator},
+};
+
+/// Harvests, then moves on in the same direction
+struct HarvestBot;
+
+impl Bot for HarvestBot {
+    fn act(&mut self, observation: &Observation) -> Action {
+        match observation.turn % 2 {
+            0 => Action::Harvest,
+            _ => Action::Move {
+                direction: Direction::Right,
+            },
+        }
+    }
+}
+
+struct IdleBot;
+
+impl Bot for IdleBot {
+    fn act(&mut self, _observation: &Observation) -> Action {
+        Action::Idle
+    }
+}
+
+fn bots() -> Vec<(String, Box<dyn Bot>)> {
+    vec![
+        ("HarvestBot".to_string(), Box::new(HarvestBot)),
+        ("IdleBot".to_string(), Box::new(IdleBot)),
+    ]
+}
+
+#[test]
+fn plays_until_max_turns() {
+    let game_settings = GameSettings {
+        max_turns: Some(20),
+        ..GameSettings::default()
+    };
+    let simulator = Simulator::new(game_settings, Ruleset::default(), bots()).unwrap();
+    let game_result = simulator.run();
+
+    assert!(matches!(
+        game_result.reason,
+        GameOverReason::MaxTurnsReached
+    ));
+    assert_eq!(game_result.turns, 20);
+    assert_eq!(game_result.ranking.len(), 2);
+}
+
+#[test]
+fn bots_observe_their_results() {
+    let game_settings = GameSettings {
+        max_turns: Some(10),
+        ..GameSettings::default()
+    };
+    let mut simulator = Simulator::new(game_settings, Ruleset::default(), bots()).unwrap();
+
+    assert!(simulator
+        .observations()
+        .all(|observation| observation.content.is_none()));
+    assert!(simulator.step().is_none());
+    assert_eq!(simulator.game().turns(), 1);
+    assert_eq!(simulator.game().players().len(), 2);
+
+    let observations = simulator.observations().collect::<Vec<_>>();
+    for observation in observations.iter() {
+        assert_eq!(observation.turn, 1);
+        assert!(!observation.swapped);
+        assert_eq!(observation.content.as_ref().unwrap().turn, 0);
+    }
+    assert!(matches!(
+        observations[0].content.as_ref().unwrap().result,
+        MsgToPlayer::Harvested { .. } | MsgToPlayer::NoHarvest
+    ));
+    assert!(matches!(
+        observations[1].content.as_ref().unwrap().result,
+        MsgToPlayer::Idled
+    ));
+}
+
+#[test]
+fn rejects_games_without_end() {
+    let simulator = Simulator::new(GameSettings::default(), Ruleset::default(), bots());
+    assert!(simulator.is_err());
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    let game_settings = GameSettings {
+        max_turns: Some(10),
+        symmetric_map: true,
+        map_size: 10,
+        ..GameSettings::default()
+    };
+    let simulator = Simulator::new(game_settings, Ruleset::default(), bots());
+    assert!(simulator.is_err());
+}
+
+#[test]
+fn rejects_more_bots_than_spots() {
+    let game_settings = GameSettings {
+        max_turns: Some(10),
+        map_size: 1,
+        visibility_radius: 0,
+        ..GameSettings::default()
+    };
+    let simulator = Simulator::new(game_settings, Ruleset::default(), bots());
+    assert_eq!(
+        simulator.err(),
+        Some("The map of `1` Cells is too small for `2` Players".to_string())
+    );
+}